@@ -40,7 +40,7 @@ pub fn invoke() {
                                         calc_average_tasks_in_one_month(p.tasks_per_month));
     basic_score += 25 * calc_proficiency(calc_number_of_days_since_first_task(p.oldest_task_timestamp),
                                          calc_average_points_award_per_task(p.average_points));
-    basic_score = basic_score / 100;
+    basic_score /= 100;
 
 
     let score = ScoreResult {
@@ -64,7 +64,7 @@ fn calc_num_of_task_completed(total_tasks:u32)->u32{
 }
 
 fn calc_amount_of_tokens_received(total_rewards_usd:f32)->u32 {
-    if total_rewards_usd == 0 as f32 {
+    if total_rewards_usd == 0.0 {
         0
     }else{
         100
@@ -72,13 +72,13 @@ fn calc_amount_of_tokens_received(total_rewards_usd:f32)->u32 {
 }
 
 fn calc_number_of_points_awarded(total_points:f32) -> u32{
-    if total_points == 0 as f32 {
+    if total_points == 0.0 {
         0
-    }else if total_points >= 1 as f32 && total_points <= 10 as f32 {
+    }else if (1.0..=10.0).contains(&total_points) {
         50
-    }else if total_points >= 11 as f32 && total_points <= 30 as f32 {
+    }else if (11.0..=30.0).contains(&total_points) {
         70
-    }else if total_points >= 31 as f32 && total_points <= 50 as f32 {
+    }else if (31.0..=50.0).contains(&total_points) {
         90
     }else {
         100
@@ -92,11 +92,11 @@ fn calc_contribution(num_of_task_completed:u32,amount_of_tokens_received:u32,num
 fn calc_number_of_days_since_most_recent(latest_task_timestamp:u32) -> u32{
     if latest_task_timestamp == 0{
         100
-    }else if latest_task_timestamp>=1 && latest_task_timestamp <=30{
+    }else if (1..=30).contains(&latest_task_timestamp){
         90
-    }else if latest_task_timestamp >=31 && latest_task_timestamp <=60{
+    }else if (31..=60).contains(&latest_task_timestamp){
         70
-    }else if latest_task_timestamp >= 61 && latest_task_timestamp <= 90{
+    }else if (61..=90).contains(&latest_task_timestamp){
         50
     }else{
         0
@@ -104,13 +104,13 @@ fn calc_number_of_days_since_most_recent(latest_task_timestamp:u32) -> u32{
 }
 
 fn calc_average_tasks_in_one_month(tasks_per_month:f32) -> u32{
-    if tasks_per_month == 0 as f32{
+    if tasks_per_month == 0.0{
         0
-    }else if tasks_per_month >0 as f32 && tasks_per_month < 1 as f32 {
+    }else if tasks_per_month >0.0 && tasks_per_month < 1.0 {
         50
-    }else if tasks_per_month >= 1 as f32 && tasks_per_month<3 as f32 {
+    }else if (1.0..3.0).contains(&tasks_per_month) {
         70
-    }else if tasks_per_month >=3 as f32 && tasks_per_month < 5 as f32 {
+    }else if (3.0..5.0).contains(&tasks_per_month) {
         90
     }else{
         100
@@ -124,11 +124,11 @@ fn calc_activeness(number_of_days_since_most_recent:u32,average_tasks_in_one_mon
 fn calc_number_of_days_since_first_task(oldest_task_timestamp:u32)->u32{
     if oldest_task_timestamp == 0{
         0
-    }else if oldest_task_timestamp >=1 && oldest_task_timestamp <=30{
+    }else if (1..=30).contains(&oldest_task_timestamp){
         50
-    }else if oldest_task_timestamp >=31 && oldest_task_timestamp <=60{
+    }else if (31..=60).contains(&oldest_task_timestamp){
         70
-    }else if oldest_task_timestamp >= 61 && oldest_task_timestamp <= 90{
+    }else if (61..=90).contains(&oldest_task_timestamp){
         90
     }else {
         100
@@ -136,13 +136,13 @@ fn calc_number_of_days_since_first_task(oldest_task_timestamp:u32)->u32{
 }
 
 fn calc_average_points_award_per_task(average_points:f32) -> u32{
-    if average_points == 0 as f32{
+    if average_points == 0.0{
         0
-    }else if average_points > 0 as f32 && average_points < 2 as f32{
+    }else if average_points > 0.0 && average_points < 2.0{
         50
-    }else if average_points >=2 as f32 && average_points <5 as f32 {
+    }else if (2.0..5.0).contains(&average_points) {
         70
-    }else if average_points >= 5 as f32 && average_points < 10 as f32{
+    }else if (5.0..10.0).contains(&average_points){
         90
     }else{
         100
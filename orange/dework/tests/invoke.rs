@@ -0,0 +1,28 @@
+use oscore::mock::{self, Outcome};
+
+fn score(request: &str) -> Outcome {
+    mock::reset();
+    mock::set_input(request);
+    mock::run(dework::invoke)
+}
+
+#[test]
+fn computes_score() {
+    let request = r#"{"data":{"total_tasks":3,"total_rewards_usd":5.0,"total_points":40.0,
+        "latest_task_timestamp":10,"tasks_per_month":4.0,"oldest_task_timestamp":100,
+        "average_points":6.0},"sig":""}"#;
+    assert_eq!(score(request), Outcome::Return(br#"{"score":95}"#.to_vec()));
+
+    let request = r#"{"data":{"total_tasks":0,"total_rewards_usd":0.0,"total_points":0.0,
+        "latest_task_timestamp":0,"tasks_per_month":0.0,"oldest_task_timestamp":0,
+        "average_points":0.0},"sig":""}"#;
+    assert_eq!(score(request), Outcome::Return(br#"{"score":12}"#.to_vec()));
+}
+
+#[test]
+fn rejects_invalid_request() {
+    match score(r#"{"data":{}}"#) {
+        Outcome::Panic(msg) => assert!(msg.contains("invalid param"), "{}", msg),
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+}
@@ -0,0 +1,24 @@
+//! Base58 decoding with the bitcoin alphabet, to parse address literals. `oscore::types::Address` has
+//! its own codec, `oscore/tests/address.rs` checks that both parse addresses the same way.
+
+pub const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+pub fn decode(input: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(input.len());
+    for c in input.bytes() {
+        let mut carry = ALPHABET.iter().position(|&a| a == c)? as u32;
+        for b in bytes.iter_mut() {
+            carry += *b as u32 * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = input.bytes().take_while(|&c| c == ALPHABET[0]).count();
+    let mut result = vec![0; zeros];
+    result.extend(bytes.iter().rev());
+    Some(result)
+}
@@ -0,0 +1,525 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    parse_quote, Attribute, Data, DataEnum, DeriveInput, Field, Fields, GenericParam, Generics,
+    Lifetime, LifetimeDef, Lit, Meta, NestedMeta, Path,
+};
+
+///Options of a field given by `#[abi(...)]`
+#[derive(Default)]
+struct FieldAttrs {
+    skip: bool,
+    with: Option<Path>,
+    type_name: Option<String>,
+    since: Option<u8>,
+}
+
+fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut res = FieldAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("abi")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new(meta.span(), "expected #[abi(...)]")),
+        };
+        for nested in list.nested.iter() {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => res.skip = true,
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("with") => {
+                    match &nv.lit {
+                        Lit::Str(lit) => res.with = Some(lit.parse()?),
+                        lit => {
+                            return Err(syn::Error::new(
+                                lit.span(),
+                                "expected a module path string",
+                            ))
+                        }
+                    }
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("type_name") => {
+                    match &nv.lit {
+                        Lit::Str(lit) => res.type_name = Some(lit.value()),
+                        lit => return Err(syn::Error::new(lit.span(), "expected a string")),
+                    }
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("since") => {
+                    res.since = Some(parse_version(&nv.lit)?)
+                }
+                _ => return Err(syn::Error::new(nested.span(), "unknown abi attribute")),
+            }
+        }
+    }
+    Ok(res)
+}
+
+fn parse_version(lit: &Lit) -> syn::Result<u8> {
+    match lit {
+        Lit::Int(int) => int.base10_parse(),
+        lit => Err(syn::Error::new(lit.span(), "expected a version number")),
+    }
+}
+
+///The version of a struct encoded in a versioned envelope, given by `#[abi(version = N)]`
+fn parse_version_attr(attrs: &[Attribute]) -> syn::Result<Option<u8>> {
+    let mut version = None;
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("abi")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new(meta.span(), "expected #[abi(...)]")),
+        };
+        for nested in list.nested.iter() {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("version") => {
+                    version = Some(parse_version(&nv.lit)?)
+                }
+                _ => return Err(syn::Error::new(nested.span(), "unknown abi attribute")),
+            }
+        }
+    }
+    Ok(version)
+}
+
+///Check the `since` of the fields against the version of the container. Older decoders skip the trailing
+///bytes of the body, so the fields must be in the order of their `since`, after the original fields.
+fn check_versions(
+    version: Option<u8>,
+    fields: &[(&Field, FieldAttrs)],
+    span: Span,
+    is_enum: bool,
+) -> syn::Result<()> {
+    if version.is_some() && is_enum {
+        return Err(syn::Error::new(span, "versioned enum is not supported"));
+    }
+    let mut last_since = None;
+    for (field, attrs) in fields.iter().filter(|(_, attrs)| !attrs.skip) {
+        if attrs.since < last_since {
+            return Err(syn::Error::new(
+                field.span(),
+                "fields must be in the order of their `since`, after the fields without `since`",
+            ));
+        }
+        last_since = attrs.since;
+        match (attrs.since, version) {
+            (Some(_), None) => {
+                return Err(syn::Error::new(
+                    field.span(),
+                    "`since` requires #[abi(version = N)] on the struct",
+                ))
+            }
+            (Some(since), Some(version)) if since > version => {
+                return Err(syn::Error::new(
+                    field.span(),
+                    "`since` is newer than the version of the struct",
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+///Check that the index of every variant fits in the one byte tag
+fn check_variants(data: &DataEnum, span: Span) -> syn::Result<()> {
+    if data.variants.len() > 256 {
+        return Err(syn::Error::new(span, "enum with more than 256 variants"));
+    }
+    Ok(())
+}
+
+fn field_attrs(fields: &Fields) -> syn::Result<Vec<(&Field, FieldAttrs)>> {
+    fields
+        .iter()
+        .map(|field| Ok((field, parse_field_attrs(&field.attrs)?)))
+        .collect()
+}
+
+fn encode_field(val: &TokenStream, attrs: &FieldAttrs) -> TokenStream {
+    match &attrs.with {
+        _ if attrs.skip => quote!(),
+        Some(with) => quote!(#with::encode(#val, sink);),
+        None => quote!(sink.write(#val);),
+    }
+}
+
+///The encoded size of a field as a `+ size` term, `None` for fields encoded `with` a module since those
+///do not provide their size
+fn size_field(val: &TokenStream, attrs: &FieldAttrs) -> Option<TokenStream> {
+    match &attrs.with {
+        _ if attrs.skip => Some(quote!()),
+        Some(_) => None,
+        None => Some(quote!(+ ::oscore::abi::Encoder::encoded_size(#val))),
+    }
+}
+
+///Decode a field, adding the path of the field to the errors
+fn decode_field(attrs: &FieldAttrs, path: &str) -> TokenStream {
+    let read = match &attrs.with {
+        _ if attrs.skip => return quote!(::core::default::Default::default()),
+        Some(with) => quote!(#with::decode(source)),
+        None => quote!(source.read()),
+    };
+    let value = quote!(#read.map_err(|e: ::oscore::abi::Error| e.context(#path))?);
+    match attrs.since {
+        // fields added after the version of the input have their default value
+        Some(since) => quote! {
+            if __version >= #since {
+                #value
+            } else {
+                ::core::default::Default::default()
+            }
+        },
+        None => value,
+    }
+}
+
+///Bind the fields of a variant to `__f0, __f1...`, skipped fields are bound to `_`. Return the pattern,
+///the writes and the size terms of the fields.
+fn bind_fields(
+    fields: &[(&Field, FieldAttrs)],
+    named: bool,
+) -> (TokenStream, Vec<TokenStream>, Option<Vec<TokenStream>>) {
+    let mut pats = Vec::new();
+    let mut encodes = Vec::new();
+    let mut sizes = Some(Vec::new());
+    for (i, (field, attrs)) in fields.iter().enumerate() {
+        let bind = if attrs.skip {
+            quote!(_)
+        } else {
+            let ident = format_ident!("__f{}", i);
+            encodes.push(encode_field(&quote!(#ident), attrs));
+            sizes = sizes.and_then(|mut sizes| {
+                sizes.push(size_field(&quote!(#ident), attrs)?);
+                Some(sizes)
+            });
+            quote!(#ident)
+        };
+        match &field.ident {
+            Some(name) if named => pats.push(quote!(#name: #bind)),
+            _ => pats.push(bind),
+        }
+    }
+    let pat = if named {
+        quote!({ #(#pats),* })
+    } else if fields.is_empty() {
+        quote!()
+    } else {
+        quote!(( #(#pats),* ))
+    };
+    (pat, encodes, sizes)
+}
+
+///Build the value from its decoded fields, `prefix` is the path of the struct or variant
+fn construct(prefix: &str, fields: &Fields, attrs: &[(&Field, FieldAttrs)]) -> TokenStream {
+    let values = attrs.iter().enumerate().map(|(i, (field, attrs))| {
+        let path = match &field.ident {
+            Some(ident) => format!("{}.{}", prefix, ident),
+            None => format!("{}.{}", prefix, i),
+        };
+        decode_field(attrs, &path)
+    });
+    match fields {
+        Fields::Named(_) => {
+            let names = attrs.iter().map(|(field, _)| &field.ident);
+            quote!({ #(#names: #values),* })
+        }
+        Fields::Unnamed(_) => quote!(( #(#values),* )),
+        Fields::Unit => quote!(),
+    }
+}
+
+fn add_bounds(generics: &Generics, bound: TokenStream) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.params.iter_mut() {
+        if let GenericParam::Type(ty) = param {
+            ty.bounds.push(parse_quote!(#bound));
+        }
+    }
+    generics
+}
+
+pub fn expand_encoder(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let version = parse_version_attr(&input.attrs)?;
+    let (body, size) = match &input.data {
+        Data::Struct(data) => {
+            let fields = field_attrs(&data.fields)?;
+            check_versions(version, &fields, input.span(), false)?;
+            let members: Vec<_> = fields
+                .iter()
+                .enumerate()
+                .map(|(i, (field, _))| match &field.ident {
+                    Some(ident) => quote!(#ident),
+                    None => {
+                        let index = syn::Index::from(i);
+                        quote!(#index)
+                    }
+                })
+                .collect();
+            let writes = members
+                .iter()
+                .zip(&fields)
+                .map(|(member, (_, attrs))| encode_field(&quote!(&self.#member), attrs));
+            let sizes: Option<Vec<_>> = members
+                .iter()
+                .zip(&fields)
+                .map(|(member, (_, attrs))| size_field(&quote!(&self.#member), attrs))
+                .collect();
+            match version {
+                Some(version) => {
+                    // fields encoded `with` a module have no size, the body is then counted by
+                    // encoding it into a temporary buffer
+                    let body_size = match &sizes {
+                        Some(sizes) => quote!(::core::option::Option::Some(0 #(#sizes)*)),
+                        None => quote!(::core::option::Option::None),
+                    };
+                    let body = quote! {
+                        ::oscore::abi::encode_versioned(sink, #version, #body_size, |sink| {
+                            #(#writes)*
+                        });
+                    };
+                    // the version byte, then the body as length prefixed bytes
+                    let size = sizes.map(|sizes| {
+                        quote! {
+                            let __body = 0 #(#sizes)*;
+                            1 + ::oscore::abi::varuint_encode_size(__body as u64) + __body
+                        }
+                    });
+                    (body, size)
+                }
+                None => (quote!(#(#writes)*), sizes.map(|sizes| quote!(0 #(#sizes)*))),
+            }
+        }
+        Data::Enum(data) => {
+            check_versions(version, &[], input.span(), true)?;
+            check_variants(data, input.span())?;
+            let mut arms = Vec::new();
+            let mut size_arms = Some(Vec::new());
+            for (i, variant) in data.variants.iter().enumerate() {
+                let ident = &variant.ident;
+                let fields = field_attrs(&variant.fields)?;
+                let named = matches!(variant.fields, Fields::Named(_));
+                let (pat, encodes, sizes) = bind_fields(&fields, named);
+                let tag = i as u8;
+                arms.push(quote! {
+                    #name::#ident #pat => {
+                        sink.write(#tag);
+                        #(#encodes)*
+                    }
+                });
+                size_arms = size_arms.and_then(|mut arms: Vec<_>| {
+                    let sizes = sizes?;
+                    arms.push(quote!(#name::#ident #pat => 1 #(#sizes)*,));
+                    Some(arms)
+                });
+            }
+            if arms.is_empty() {
+                (quote!(match *self {}), Some(quote!(match *self {})))
+            } else {
+                let size = size_arms.map(|arms| {
+                    quote! {
+                        match self {
+                            #(#arms)*
+                        }
+                    }
+                });
+                let body = quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                };
+                (body, size)
+            }
+        }
+        Data::Union(_) => return Err(syn::Error::new(input.span(), "union is not supported")),
+    };
+    // without the size of every field, the default implementation encodes to count the bytes
+    let size = size.map(|size| {
+        quote! {
+            #[allow(unused_variables)]
+            fn encoded_size(&self) -> usize {
+                #size
+            }
+        }
+    });
+    let generics = add_bounds(&input.generics, quote!(::oscore::abi::Encoder));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::oscore::abi::Encoder for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn encode(&self, sink: &mut ::oscore::abi::Sink) {
+                #body
+            }
+
+            #size
+        }
+    })
+}
+
+pub fn expand_decoder(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let version = parse_version_attr(&input.attrs)?;
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let fields = field_attrs(&data.fields)?;
+            check_versions(version, &fields, input.span(), false)?;
+            let value = construct(&name.to_string(), &data.fields, &fields);
+            match version {
+                // the fields are read from the body, trailing fields of newer versions are skipped
+                Some(_) => quote! {
+                    ::oscore::abi::decode_versioned(source, |__version, source| Ok(#name #value))
+                },
+                None => quote!(Ok(#name #value)),
+            }
+        }
+        Data::Enum(data) => {
+            check_versions(version, &[], input.span(), true)?;
+            check_variants(data, input.span())?;
+            let mut arms = Vec::new();
+            for (i, variant) in data.variants.iter().enumerate() {
+                let ident = &variant.ident;
+                let fields = field_attrs(&variant.fields)?;
+                let prefix = format!("{}::{}", name, ident);
+                let value = construct(&prefix, &variant.fields, &fields);
+                let tag = i as u8;
+                arms.push(quote!(#tag => Ok(#name::#ident #value),));
+            }
+            quote! {
+                let pos = source.position();
+                match source.read_byte()? {
+                    #(#arms)*
+                    _ => Err(::oscore::abi::Error::new(::oscore::abi::ErrorKind::IrregularData, pos)),
+                }
+            }
+        }
+        Data::Union(_) => return Err(syn::Error::new(input.span(), "union is not supported")),
+    };
+
+    // borrowed fields are decoded with the lifetime of the first lifetime parameter
+    let lifetimes: Vec<&LifetimeDef> = input.generics.lifetimes().collect();
+    let (lifetime, mut generics) = match lifetimes.first() {
+        Some(first) => (first.lifetime.clone(), input.generics.clone()),
+        None => {
+            let lifetime = Lifetime::new("'__a", Span::call_site());
+            let mut generics = input.generics.clone();
+            generics.params.insert(
+                0,
+                GenericParam::Lifetime(LifetimeDef::new(lifetime.clone())),
+            );
+            (lifetime, generics)
+        }
+    };
+    for other in lifetimes.iter().skip(1) {
+        let other = &other.lifetime;
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#lifetime: #other));
+    }
+    let generics = add_bounds(&generics, quote!(::oscore::abi::Decoder<#lifetime>));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::oscore::abi::Decoder<#lifetime> for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn decode(source: &mut ::oscore::abi::Source<#lifetime>) -> ::core::result::Result<Self, ::oscore::abi::Error> {
+                #body
+            }
+        }
+    })
+}
+
+///Describe the fields which are encoded, `with` fields are described by their `type_name` attribute
+fn describe_fields(fields: &[(&Field, FieldAttrs)]) -> (Vec<TokenStream>, Vec<TokenStream>) {
+    let mut defs = Vec::new();
+    let mut registers = Vec::new();
+    for (i, (field, attrs)) in fields.iter().enumerate() {
+        if attrs.skip {
+            continue;
+        }
+        let name = match &field.ident {
+            Some(ident) => ident.to_string(),
+            None => i.to_string(),
+        };
+        let ty = &field.ty;
+        let type_name = match (&attrs.type_name, &attrs.with) {
+            (Some(type_name), _) => quote!(#type_name.to_string()),
+            (None, Some(with)) => {
+                let with = quote!(#with).to_string().replace(' ', "");
+                quote!(#with.to_string())
+            }
+            (None, None) => {
+                registers.push(quote!(<#ty as ::oscore::abi::meta::TypeInfo>::register(registry);));
+                quote!(<#ty as ::oscore::abi::meta::TypeInfo>::type_name())
+            }
+        };
+        defs.push(quote!(::oscore::abi::meta::FieldDef::new(#name, #type_name)));
+    }
+    (defs, registers)
+}
+
+pub fn expand_type_info(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let mut registers = Vec::new();
+    let def = match &input.data {
+        Data::Struct(data) => {
+            let fields = field_attrs(&data.fields)?;
+            let (defs, regs) = describe_fields(&fields);
+            registers.extend(regs);
+            quote! {
+                ::oscore::abi::meta::TypeDef::Struct {
+                    name: Self::type_name(),
+                    fields: vec![#(#defs),*],
+                }
+            }
+        }
+        Data::Enum(data) => {
+            let mut variants = Vec::new();
+            for (i, variant) in data.variants.iter().enumerate() {
+                let fields = field_attrs(&variant.fields)?;
+                let (defs, regs) = describe_fields(&fields);
+                registers.extend(regs);
+                let variant_name = variant.ident.to_string();
+                let index = i as u8;
+                variants.push(quote! {
+                    ::oscore::abi::meta::VariantDef {
+                        name: #variant_name.to_string(),
+                        index: #index,
+                        fields: vec![#(#defs),*],
+                    }
+                });
+            }
+            quote! {
+                ::oscore::abi::meta::TypeDef::Enum {
+                    name: Self::type_name(),
+                    variants: vec![#(#variants),*],
+                }
+            }
+        }
+        Data::Union(_) => return Err(syn::Error::new(input.span(), "union is not supported")),
+    };
+    let params = input.generics.type_params().map(|param| {
+        let ident = &param.ident;
+        quote!(<#ident as ::oscore::abi::meta::TypeInfo>::type_name())
+    });
+    let generics = add_bounds(&input.generics, quote!(::oscore::abi::meta::TypeInfo));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::oscore::abi::meta::TypeInfo for #name #ty_generics #where_clause {
+            fn type_name() -> String {
+                let params: Vec<String> = vec![#(#params),*];
+                if params.is_empty() {
+                    #name_str.to_string()
+                } else {
+                    format!("{}<{}>", #name_str, params.join(", "))
+                }
+            }
+
+            fn register(registry: &mut ::oscore::abi::meta::Registry) {
+                if registry.insert(#def) {
+                    #(#registers)*
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,178 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{FnArg, ImplItem, ImplItemMethod, ItemImpl, Pat, ReturnType, Type, Visibility};
+
+///The type of the value returned by the method, the `T` of `Result<T, E>`
+fn returned_type(output: &ReturnType) -> TokenStream {
+    match output {
+        ReturnType::Default => quote!(()),
+        ReturnType::Type(_, ty) => quote!(<#ty as ::oscore::runtime::MethodResult>::Value),
+    }
+}
+
+///Describe the method in the ABI manifest, and register the types it uses
+fn describe_method(method: &ImplItemMethod) -> (TokenStream, Vec<TokenStream>) {
+    let name_str = method.sig.ident.to_string();
+    let mut args = Vec::new();
+    let mut types = Vec::new();
+    for input in method.sig.inputs.iter() {
+        if let FnArg::Typed(arg) = input {
+            let arg_name = match &*arg.pat {
+                Pat::Ident(pat) => pat.ident.to_string(),
+                _ => format!("arg{}", args.len()),
+            };
+            let ty = &arg.ty;
+            args.push(quote! {
+                ::oscore::abi::meta::FieldDef::new(
+                    #arg_name,
+                    <#ty as ::oscore::abi::meta::TypeInfo>::type_name(),
+                )
+            });
+            types.push(quote!(<#ty as ::oscore::abi::meta::TypeInfo>::register(&mut registry);));
+        }
+    }
+    let ret = returned_type(&method.sig.output);
+    types.push(quote!(<#ret as ::oscore::abi::meta::TypeInfo>::register(&mut registry);));
+    let def = quote! {
+        ::oscore::abi::meta::MethodDef {
+            name: #name_str.to_string(),
+            args: vec![#(#args),*],
+            ret: <#ret as ::oscore::abi::meta::TypeInfo>::type_name(),
+        }
+    };
+    (def, types)
+}
+
+fn dispatch_method(self_ty: &Type, method: &ImplItemMethod) -> syn::Result<TokenStream> {
+    let name = &method.sig.ident;
+    let name_str = name.to_string();
+    let mut receiver = None;
+    let mut decode_args = Vec::new();
+    let mut args = Vec::new();
+    for input in method.sig.inputs.iter() {
+        match input {
+            FnArg::Receiver(recv) => {
+                if recv.reference.is_none() {
+                    return Err(syn::Error::new(
+                        recv.span(),
+                        "contract methods take `&self` or `&mut self`",
+                    ));
+                }
+                receiver = Some(recv.mutability.is_some());
+            }
+            FnArg::Typed(arg) => {
+                let ident = format_ident!("arg{}", args.len());
+                let ty = &arg.ty;
+                decode_args.push(quote! {
+                    let #ident: #ty = source
+                        .read()
+                        .unwrap_or_else(|_| ::oscore::runtime::panic("invalid param"));
+                });
+                args.push(ident);
+            }
+        }
+    }
+    let call = match receiver {
+        None => quote!(<#self_ty>::#name(#(#args),*)),
+        Some(mutable) => {
+            let binding = if mutable {
+                quote!(let mut)
+            } else {
+                quote!(let)
+            };
+            quote! {{
+                #binding contract = <#self_ty as ::core::default::Default>::default();
+                contract.#name(#(#args),*)
+            }}
+        }
+    };
+    Ok(quote! {
+        #name_str => {
+            #(#decode_args)*
+            source
+                .finish()
+                .unwrap_or_else(|_| ::oscore::runtime::panic("invalid param"));
+            let res = #call;
+            ::oscore::runtime::MethodResult::ret(res)
+        }
+    })
+}
+
+///Parse the options of `#[contract(...)]`, return whether the ABI manifest is generated
+fn parse_options(attr: TokenStream) -> syn::Result<bool> {
+    if attr.is_empty() {
+        return Ok(false);
+    }
+    let ident: syn::Ident = syn::parse2(attr)?;
+    if ident != "abi" {
+        return Err(syn::Error::new(
+            ident.span(),
+            "unknown contract option, expected `abi`",
+        ));
+    }
+    Ok(true)
+}
+
+pub fn expand(attr: TokenStream, item: ItemImpl) -> syn::Result<TokenStream> {
+    let abi = parse_options(attr)?;
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            item.generics.span(),
+            "contract can not be generic",
+        ));
+    }
+    let self_ty = &item.self_ty;
+    let self_name = quote!(#self_ty).to_string().replace(' ', "");
+    let mut arms = Vec::new();
+    let mut methods = Vec::new();
+    let mut registers = Vec::new();
+    for impl_item in item.items.iter() {
+        if let ImplItem::Method(method) = impl_item {
+            if let Visibility::Public(_) = method.vis {
+                arms.push(dispatch_method(self_ty, method)?);
+                let (def, types) = describe_method(method);
+                methods.push(def);
+                registers.extend(types);
+            }
+        }
+    }
+    let abi = if abi {
+        quote! {
+            #[cfg(not(target_arch = "wasm32"))]
+            impl ::oscore::abi::meta::Contract for #self_ty {
+                fn abi() -> ::oscore::abi::meta::ContractAbi {
+                    let mut registry = ::oscore::abi::meta::Registry::new();
+                    #(#registers)*
+                    ::oscore::abi::meta::ContractAbi {
+                        name: #self_name.to_string(),
+                        methods: vec![#(#methods),*],
+                        types: registry.into_types(),
+                    }
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+    Ok(quote! {
+        #item
+
+        #[no_mangle]
+        pub fn invoke() {
+            ::oscore::set_panic_handler();
+            let input = ::oscore::runtime::input();
+            let mut source = ::oscore::abi::Source::new(&input)
+                .with_limits(::oscore::abi::DecodeLimits::default());
+            let method: &str = source
+                .read()
+                .unwrap_or_else(|_| ::oscore::runtime::panic("invalid method"));
+            match method {
+                #(#arms)*
+                _ => ::oscore::runtime::panic("method not found"),
+            }
+        }
+
+        #abi
+    })
+}
@@ -0,0 +1,252 @@
+extern crate proc_macro;
+
+mod base58;
+mod codec;
+mod contract;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use sha2::{Digest, Sha256};
+use syn::{parse_macro_input, DeriveInput, ItemImpl, LitStr};
+
+///The version byte of base58 addresses, the same as in `oscore::types::Address`
+const ADDR_VERSION: u8 = 0x17;
+
+fn decode_address(input: &str) -> Result<[u8; 20], String> {
+    let data = base58::decode(input).ok_or_else(|| {
+        let c = input.bytes().find(|c| !base58::ALPHABET.contains(c));
+        format!(
+            "invalid base58 character '{}'",
+            c.unwrap_or_default() as char
+        )
+    })?;
+    if data.len() != 25 {
+        return Err(format!(
+            "invalid address length {}, expected 25 bytes",
+            data.len()
+        ));
+    }
+    if data[0] != ADDR_VERSION {
+        return Err(format!("invalid address version {}", data[0]));
+    }
+    let checksum = Sha256::digest(&Sha256::digest(&data[..21]));
+    if checksum[..4] != data[21..] {
+        return Err("invalid address checksum".to_string());
+    }
+    let mut addr = [0; 20];
+    addr.copy_from_slice(&data[1..21]);
+    Ok(addr)
+}
+
+///Parse a base58 address literal at compile time into an `oscore::types::Address`.
+/// # Example
+/// ```
+/// use oscore::macros::base58;
+/// use oscore::types::Address;
+///
+/// let mut one = [0; 20];
+/// one[19] = 1;
+/// assert_eq!(base58!("AFmseVrdL9f9oyCzZefL9tG6UbvhUMqNMV"), Address::new(one));
+/// ```
+///Malformed addresses are compile errors.
+/// ```compile_fail
+/// let addr = oscore::macros::base58!("AFmseVrdL9f9oyCzZefL9tG6UbvhUMqNM0");
+/// ```
+#[proc_macro]
+pub fn base58(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    match decode_address(&lit.value()) {
+        Ok(addr) => {
+            let bytes = addr.iter();
+            quote!(::oscore::types::Address::new([#(#bytes),*])).into()
+        }
+        Err(msg) => syn::Error::new(lit.span(), msg).to_compile_error().into(),
+    }
+}
+
+///Generate the `invoke` entry point of the contract from an impl block.
+///
+///The input of the contract is the method name encoded as `&str`, followed by the arguments encoded by
+///`abi::Sink`. Public methods are dispatched by name, methods with a `&self` or `&mut self` receiver are
+///called on `Default::default()`. The input is decoded within the default `abi::DecodeLimits`, and input
+///with bytes left after the arguments is rejected as an invalid param. The return value is finished
+///through `runtime::MethodResult`: it is encoded by `abi::Sink` and returned, or passed to
+///`runtime::ret_result` when it is a `Result`, including through a type alias.
+///
+///With `#[contract(abi)]`, the impl block also implements `abi::meta::Contract` on native targets to get
+///the ABI manifest of the contract. The types of all the arguments and returned values of the public
+///methods must then implement `abi::meta::TypeInfo`. `cargo run --example abi -p oscore` prints the
+///manifest of an example contract. Other options are rejected:
+/// ```compile_fail
+/// struct Score;
+///
+/// #[oscore::macros::contract(json)]
+/// impl Score {}
+/// ```
+/// # Example
+/// ```no_run
+/// use oscore::error::ErrorInfo;
+/// use oscore::macros::contract;
+///
+/// struct Score;
+///
+/// #[contract]
+/// impl Score {
+///     pub fn add(a: u32, b: u32) -> u32 {
+///         a + b
+///     }
+///
+///     pub fn check(score: u32) -> Result<bool, ErrorInfo> {
+///         if score > 100 {
+///             return Err(ErrorInfo { code: 1, message: "invalid score".to_string() });
+///         }
+///         Ok(score >= 60)
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn contract(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemImpl);
+    contract::expand(attr.into(), item)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+///Derive `abi::Encoder`, encoding struct fields in declaration order and enums as the index of the
+///variant in one byte followed by its fields.
+///
+///Fields accept `#[abi(skip)]` to leave them out, and `#[abi(with = "module")]` to encode them with
+///`module::encode(&T, &mut Sink)`.
+///
+///Structs with `#[abi(version = N)]` are written in the envelope of `abi::encode_versioned`, and fields
+///added in later versions are marked with `#[abi(since = N)]`. They follow the other fields, in the order
+///of their `since`.
+///
+///`Encoder::encoded_size` is computed from the sizes of the fields, unless a field is encoded `with` a
+///module, in which case the value is encoded to count its bytes.
+/// # Example
+/// ```
+/// use oscore::abi::{Decoder, Encoder, Sink, Source};
+///
+/// #[derive(Encoder, Decoder, Debug, PartialEq)]
+/// enum Task {
+///     Open { id: u32 },
+///     Done(u32, #[abi(skip)] bool),
+/// }
+///
+/// let mut sink = Sink::new(0);
+/// sink.write(Task::Open { id: 7 });
+/// assert_eq!(sink.bytes(), [0, 7, 0, 0, 0]);
+/// assert_eq!(Task::Done(1, true).encoded_size(), 5);
+/// let mut source = Source::new(sink.bytes());
+/// assert_eq!(source.read::<Task>().unwrap(), Task::Open { id: 7 });
+/// ```
+#[proc_macro_derive(Encoder, attributes(abi))]
+pub fn derive_encoder(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    codec::expand_encoder(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+///Derive `abi::Decoder`, the counterpart of `#[derive(Encoder)]`.
+///
+///Skipped fields are filled with `Default::default()`, and fields with `#[abi(with = "module")]` are
+///decoded by `module::decode(&mut Source) -> Result<T, Error>`. Errors of the fields are given their
+///path, such as `Request.rate` or `Shape::Circle.0`.
+///
+///Versioned structs skip the trailing fields of newer versions, and fields with `#[abi(since = N)]`
+///are filled with `Default::default()` when the input is older than `N`.
+///
+///The tag of an enum is one byte, so enums have at most 256 variants:
+/// ```compile_fail
+/// # use oscore::abi::Decoder;
+/// #[derive(Decoder)]
+/// enum Opcode {
+/// #     V0, V1, V2, V3, V4, V5, V6, V7, V8, V9, V10, V11, V12, V13, V14, V15,
+/// #     V16, V17, V18, V19, V20, V21, V22, V23, V24, V25, V26, V27, V28, V29, V30, V31,
+/// #     V32, V33, V34, V35, V36, V37, V38, V39, V40, V41, V42, V43, V44, V45, V46, V47,
+/// #     V48, V49, V50, V51, V52, V53, V54, V55, V56, V57, V58, V59, V60, V61, V62, V63,
+/// #     V64, V65, V66, V67, V68, V69, V70, V71, V72, V73, V74, V75, V76, V77, V78, V79,
+/// #     V80, V81, V82, V83, V84, V85, V86, V87, V88, V89, V90, V91, V92, V93, V94, V95,
+/// #     V96, V97, V98, V99, V100, V101, V102, V103, V104, V105, V106, V107, V108, V109, V110, V111,
+/// #     V112, V113, V114, V115, V116, V117, V118, V119, V120, V121, V122, V123, V124, V125, V126, V127,
+/// #     V128, V129, V130, V131, V132, V133, V134, V135, V136, V137, V138, V139, V140, V141, V142, V143,
+/// #     V144, V145, V146, V147, V148, V149, V150, V151, V152, V153, V154, V155, V156, V157, V158, V159,
+/// #     V160, V161, V162, V163, V164, V165, V166, V167, V168, V169, V170, V171, V172, V173, V174, V175,
+/// #     V176, V177, V178, V179, V180, V181, V182, V183, V184, V185, V186, V187, V188, V189, V190, V191,
+/// #     V192, V193, V194, V195, V196, V197, V198, V199, V200, V201, V202, V203, V204, V205, V206, V207,
+/// #     V208, V209, V210, V211, V212, V213, V214, V215, V216, V217, V218, V219, V220, V221, V222, V223,
+/// #     V224, V225, V226, V227, V228, V229, V230, V231, V232, V233, V234, V235, V236, V237, V238, V239,
+/// #     V240, V241, V242, V243, V244, V245, V246, V247, V248, V249, V250, V251, V252, V253, V254, V255,
+/// #     V256,
+/// }
+/// ```
+/// # Example
+/// ```
+/// use oscore::abi::{Decoder, Encoder, Error, Sink, Source};
+///
+/// mod percent {
+///     use oscore::abi::{Error, Sink, Source};
+///
+///     pub fn encode(val: &f32, sink: &mut Sink) {
+///         sink.write((val * 100.0) as u32);
+///     }
+///
+///     pub fn decode(source: &mut Source) -> Result<f32, Error> {
+///         Ok(source.read::<u32>()? as f32 / 100.0)
+///     }
+/// }
+///
+/// #[derive(Encoder, Decoder, Debug, PartialEq)]
+/// struct Request<'a> {
+///     sig: &'a str,
+///     #[abi(with = "percent")]
+///     rate: f32,
+/// }
+///
+/// let mut sink = Sink::new(0);
+/// sink.write(Request { sig: "ok", rate: 0.5 });
+/// let mut source = Source::new(sink.bytes());
+/// assert_eq!(source.read::<Request>().unwrap(), Request { sig: "ok", rate: 0.5 });
+/// ```
+#[proc_macro_derive(Decoder, attributes(abi))]
+pub fn derive_decoder(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    codec::expand_decoder(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+///Derive `abi::meta::TypeInfo`, describing the layout encoded by `#[derive(Encoder, Decoder)]` in the ABI
+///manifest.
+///
+///Skipped fields are left out. Fields encoded `with` a module are described by
+///`#[abi(type_name = "...")]`, or by the module path if it is missing.
+/// # Example
+/// ```
+/// use oscore::abi::meta::{Registry, TypeDef, TypeInfo};
+///
+/// #[derive(TypeInfo)]
+/// struct Request {
+///     data: Vec<u32>,
+///     sig: String,
+/// }
+///
+/// let mut registry = Registry::new();
+/// Request::register(&mut registry);
+/// match &registry.into_types()[0] {
+///     TypeDef::Struct { name, fields } => {
+///         assert_eq!(name, "Request");
+///         assert_eq!(fields[0].ty, "Vec<u32>");
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+#[proc_macro_derive(TypeInfo, attributes(abi))]
+pub fn derive_type_info(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    codec::expand_type_info(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
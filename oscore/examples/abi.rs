@@ -0,0 +1,37 @@
+//! Print the ABI manifest of a contract as JSON, run with `cargo run --example abi -p oscore`.
+//!
+//! A contract crate does the same from an example or a binary built with the `std` feature of oscore.
+use oscore::abi::meta::{Contract, TypeInfo};
+use oscore::abi::{Decoder, Encoder};
+use oscore::macros::contract;
+use oscore::storage::StorageMap;
+
+#[derive(Clone, Encoder, Decoder, TypeInfo)]
+pub struct Task {
+    pub title: String,
+    pub reward: u64,
+}
+
+pub struct TaskBoard;
+
+#[contract(abi)]
+impl TaskBoard {
+    pub fn post(id: u32, task: Task) -> bool {
+        let mut tasks: StorageMap<u32, Task> = StorageMap::new("tasks");
+        if tasks.contains_key(&id) {
+            return false;
+        }
+        tasks.insert(&id, task);
+        tasks.flush();
+        true
+    }
+
+    pub fn get(id: u32) -> Option<Task> {
+        let mut tasks: StorageMap<u32, Task> = StorageMap::new("tasks");
+        tasks.get(&id).cloned()
+    }
+}
+
+fn main() {
+    println!("{}", TaskBoard::abi().to_json());
+}
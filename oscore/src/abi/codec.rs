@@ -1,6 +1,8 @@
+use super::{varuint_encode_size, Error, ErrorKind, Sink, Source};
 use super::{Decoder, Encoder};
-use super::{Error, Sink, Source};
 use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
 
 impl<'a> Decoder<'a> for u8 {
     fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
@@ -16,21 +18,79 @@ impl<'a> Decoder<'a> for &'a [u8] {
 
 impl<'a, T: Decoder<'a>> Decoder<'a> for Vec<T> {
     fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
-        let len = source.read_varuint()?;
-        let mut value = Vec::with_capacity(cmp::min(len, 1024) as usize);
-        for _i in 0..len {
-            value.push(source.read::<T>()?);
+        let len = source.read_len::<T>()?;
+        let mut value = Vec::with_capacity(cmp::min(len, 1024));
+        for i in 0..len {
+            value.push(
+                source
+                    .read::<T>()
+                    .map_err(|e| e.context(format!("Vec[{}]", i)))?,
+            );
         }
 
         Ok(value)
     }
 }
 
+/// Maps and sets are encoded as their length followed by the entries in ascending order of the keys, so
+/// decoders reject duplicate or unsorted keys to keep the encoding canonical
+fn decode_sorted<'a, K, T, F>(source: &mut Source<'a>, key: F) -> Result<Vec<T>, Error>
+where
+    K: Ord,
+    T: Decoder<'a>,
+    F: Fn(&T) -> &K,
+{
+    let pos = source.position();
+    let entries: Vec<T> = source.read()?;
+    if entries.windows(2).all(|w| key(&w[0]) < key(&w[1])) {
+        Ok(entries)
+    } else {
+        Err(Error::new(ErrorKind::IrregularData, pos))
+    }
+}
+
+impl<'a, K: Decoder<'a> + Ord, V: Decoder<'a>> Decoder<'a> for BTreeMap<K, V> {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let entries = decode_sorted(source, |(k, _): &(K, V)| k)?;
+        Ok(entries.into_iter().collect())
+    }
+}
+
+impl<'a, K: Decoder<'a> + Ord> Decoder<'a> for BTreeSet<K> {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let entries = decode_sorted(source, |k: &K| k)?;
+        Ok(entries.into_iter().collect())
+    }
+}
+
+impl<'a, K, V, S> Decoder<'a> for HashMap<K, V, S>
+where
+    K: Decoder<'a> + Ord + Hash,
+    V: Decoder<'a>,
+    S: BuildHasher + Default,
+{
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let entries = decode_sorted(source, |(k, _): &(K, V)| k)?;
+        Ok(entries.into_iter().collect())
+    }
+}
+
+impl<'a, K, S> Decoder<'a> for HashSet<K, S>
+where
+    K: Decoder<'a> + Ord + Hash,
+    S: BuildHasher + Default,
+{
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let entries = decode_sorted(source, |k: &K| k)?;
+        Ok(entries.into_iter().collect())
+    }
+}
+
 impl<'a, T: Decoder<'a>> Decoder<'a> for Option<T> {
     fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
         let is_val: bool = source.read()?;
         if is_val {
-            Ok(Some(source.read()?))
+            Ok(Some(source.read().map_err(|e: Error| e.context("Option"))?))
         } else {
             Ok(None)
         }
@@ -39,14 +99,16 @@ impl<'a, T: Decoder<'a>> Decoder<'a> for Option<T> {
 
 impl<'a> Decoder<'a> for &'a str {
     fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let pos = source.position();
         let buf = source.read_bytes()?;
-        std::str::from_utf8(buf).map_err(|_| Error::InvalidUtf8)
+        std::str::from_utf8(buf).map_err(|_| Error::new(ErrorKind::InvalidUtf8, pos))
     }
 }
 
 impl<'a> Decoder<'a> for String {
     fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
         let s: &str = source.read()?;
+        source.alloc(s.len())?;
         Ok(s.to_string())
     }
 }
@@ -81,39 +143,43 @@ impl<'a> Decoder<'a> for u128 {
     }
 }
 
-impl<'a> Decoder<'a> for i128 {
-    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
-        Ok(source.read_u128()? as i128)
-    }
-}
-
 impl Encoder for u8 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_byte(*self)
     }
+
+    fn encoded_size(&self) -> usize {
+        1
+    }
 }
 
 impl Encoder for u16 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_u16(*self)
     }
+
+    fn encoded_size(&self) -> usize {
+        2
+    }
 }
 
 impl Encoder for u32 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_u32(*self)
     }
+
+    fn encoded_size(&self) -> usize {
+        4
+    }
 }
 
 impl Encoder for u128 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_bytes(&self.to_le_bytes())
     }
-}
 
-impl Encoder for i128 {
-    fn encode(&self, sink: &mut Sink) {
-        sink.write_bytes(&self.to_le_bytes())
+    fn encoded_size(&self) -> usize {
+        16
     }
 }
 
@@ -121,18 +187,30 @@ impl Encoder for u64 {
     fn encode(&self, sink: &mut Sink) {
         sink.write_u64(*self)
     }
+
+    fn encoded_size(&self) -> usize {
+        8
+    }
 }
 
 impl Encoder for bool {
     fn encode(&self, sink: &mut Sink) {
         sink.write_bool(*self)
     }
+
+    fn encoded_size(&self) -> usize {
+        1
+    }
 }
 
 impl<T: Encoder> Encoder for Vec<T> {
     fn encode(&self, sink: &mut Sink) {
         self.as_slice().encode(sink);
     }
+
+    fn encoded_size(&self) -> usize {
+        self.as_slice().encoded_size()
+    }
 }
 
 impl<T> Encoder for &[T]
@@ -145,6 +223,71 @@ where
             sink.write(item);
         }
     }
+
+    fn encoded_size(&self) -> usize {
+        let items: usize = self.iter().map(Encoder::encoded_size).sum();
+        varuint_encode_size(self.len() as u64) + items
+    }
+}
+
+impl<K: Encoder, V: Encoder> Encoder for BTreeMap<K, V> {
+    fn encode(&self, sink: &mut Sink) {
+        sink.write_varuint(self.len() as u64);
+        for entry in self.iter() {
+            sink.write(entry);
+        }
+    }
+
+    fn encoded_size(&self) -> usize {
+        let entries: usize = self
+            .iter()
+            .map(|(k, v)| k.encoded_size() + v.encoded_size())
+            .sum();
+        varuint_encode_size(self.len() as u64) + entries
+    }
+}
+
+impl<K: Encoder> Encoder for BTreeSet<K> {
+    fn encode(&self, sink: &mut Sink) {
+        sink.write_varuint(self.len() as u64);
+        for key in self.iter() {
+            sink.write(key);
+        }
+    }
+
+    fn encoded_size(&self) -> usize {
+        let keys: usize = self.iter().map(Encoder::encoded_size).sum();
+        varuint_encode_size(self.len() as u64) + keys
+    }
+}
+
+impl<K: Encoder + Ord, V: Encoder, S> Encoder for HashMap<K, V, S> {
+    fn encode(&self, sink: &mut Sink) {
+        let mut entries: Vec<(&K, &V)> = self.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        entries.encode(sink);
+    }
+
+    fn encoded_size(&self) -> usize {
+        let entries: usize = self
+            .iter()
+            .map(|(k, v)| k.encoded_size() + v.encoded_size())
+            .sum();
+        varuint_encode_size(self.len() as u64) + entries
+    }
+}
+
+impl<K: Encoder + Ord, S> Encoder for HashSet<K, S> {
+    fn encode(&self, sink: &mut Sink) {
+        let mut keys: Vec<&K> = self.iter().collect();
+        keys.sort_unstable();
+        keys.encode(sink);
+    }
+
+    fn encoded_size(&self) -> usize {
+        let keys: usize = self.iter().map(Encoder::encoded_size).sum();
+        varuint_encode_size(self.len() as u64) + keys
+    }
 }
 
 impl<T: Encoder> Encoder for Option<T> {
@@ -156,6 +299,10 @@ impl<T: Encoder> Encoder for Option<T> {
             sink.write(false);
         }
     }
+
+    fn encoded_size(&self) -> usize {
+        1 + self.as_ref().map_or(0, Encoder::encoded_size)
+    }
 }
 
 impl Encoder for &str {
@@ -163,20 +310,94 @@ impl Encoder for &str {
         sink.write_varuint(self.len() as u64);
         sink.write_bytes(self.as_bytes());
     }
+
+    fn encoded_size(&self) -> usize {
+        varuint_encode_size(self.len() as u64) + self.len()
+    }
 }
 
 impl Encoder for String {
     fn encode(&self, sink: &mut Sink) {
         self.as_str().encode(sink)
     }
+
+    fn encoded_size(&self) -> usize {
+        self.as_str().encoded_size()
+    }
 }
 
 impl<T: Encoder> Encoder for &T {
     fn encode(&self, sink: &mut Sink) {
         (*self).encode(sink)
     }
+
+    fn encoded_size(&self) -> usize {
+        (*self).encoded_size()
+    }
 }
 
+/// Signed integers are encoded in little endian two's complement
+macro_rules! impl_abi_codec_signed {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a> Decoder<'a> for $ty {
+                fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+                    let mut buf = [0; std::mem::size_of::<$ty>()];
+                    source.read_into(&mut buf)?;
+                    Ok(<$ty>::from_le_bytes(buf))
+                }
+            }
+
+            impl Encoder for $ty {
+                fn encode(&self, sink: &mut Sink) {
+                    sink.write_bytes(&self.to_le_bytes())
+                }
+
+                fn encoded_size(&self) -> usize {
+                    std::mem::size_of::<$ty>()
+                }
+            }
+        )*
+    };
+}
+
+impl_abi_codec_signed!(i8, i16, i32, i64, i128);
+
+/// Floats are encoded as the little endian bytes of their bits. NaN and infinities are rejected, and
+/// encoding them panics.
+#[cfg(feature = "float")]
+macro_rules! impl_abi_codec_float {
+    ($($ty:ty => $bits:ty),*) => {
+        $(
+            impl<'a> Decoder<'a> for $ty {
+                fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+                    let pos = source.position();
+                    let val = <$ty>::from_bits(source.read::<$bits>()?);
+                    if val.is_finite() {
+                        Ok(val)
+                    } else {
+                        Err(Error::new(ErrorKind::IrregularData, pos))
+                    }
+                }
+            }
+
+            impl Encoder for $ty {
+                fn encode(&self, sink: &mut Sink) {
+                    assert!(self.is_finite(), "can not encode {}", self);
+                    sink.write(self.to_bits())
+                }
+
+                fn encoded_size(&self) -> usize {
+                    std::mem::size_of::<$ty>()
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "float")]
+impl_abi_codec_float!(f32 => u32, f64 => u64);
+
 macro_rules! impl_abi_codec_fixed_array {
     () => {};
     ($num:expr) => {
@@ -192,6 +413,10 @@ macro_rules! impl_abi_codec_fixed_array {
             fn encode(&self, sink: &mut Sink) {
                 sink.write_bytes(self)
             }
+
+            fn encoded_size(&self) -> usize {
+                $num
+            }
         }
     } ;
     ($num:expr, $($tail:expr),*) => {
@@ -205,26 +430,6 @@ impl_abi_codec_fixed_array!(
     27, 28, 29, 30, 31, 32
 );
 
-/// reference:
-/// 1. https://github.com/rust-lang/rust/issues/24830
-/// 2. https://github.com/rust-lang/rust/blob/8f5b5f94dcdb9884737dfbc8efd893d1d70f0b14/src/libcore/hash/mod.rs#L239
-/// 3. https://github.com/rust-num/num/pull/89/files
-macro_rules! for_each_tuple_ {
-    ($m:ident !!) => {
-        $m! { }
-    };
-    ($m:ident !! $h:ident, $($t:ident,)*) => {
-        $m! { $h $($t)* }
-        for_each_tuple_! { $m !! $($t,)* }
-    }
-}
-macro_rules! for_each_tuple {
-    ($($m:tt)*) => {
-        macro_rules! m { $($m)* }
-        for_each_tuple_! { m !! A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, }
-    }
-}
-
 //trace_macros!(true);
 for_each_tuple! {
     ($($item:ident)*) => {
@@ -240,6 +445,12 @@ for_each_tuple! {
                 let ($($item,)*) = self;
                 $(_sink.write($item);)*
             }
+
+            fn encoded_size(&self) -> usize {
+                #[allow(non_snake_case)]
+                let ($($item,)*) = self;
+                0 $(+ $item.encoded_size())*
+            }
         }
     }
 }
@@ -0,0 +1,107 @@
+use std::borrow::Cow;
+use std::fmt;
+
+///The reason a decoding failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    UnexpectedEOF,
+    IrregularData,
+    InvalidUtf8,
+    TypeInconsistency,
+    LengthInconsistency,
+    ///A limit of `DecodeLimits` was exceeded
+    LimitExceeded,
+    ///The input has bytes left after the decoded value
+    TrailingBytes,
+}
+
+impl ErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::UnexpectedEOF => "unexpected end of input",
+            ErrorKind::IrregularData => "irregular data",
+            ErrorKind::InvalidUtf8 => "invalid utf8",
+            ErrorKind::TypeInconsistency => "type inconsistency",
+            ErrorKind::LengthInconsistency => "length inconsistency",
+            ErrorKind::LimitExceeded => "decode limit exceeded",
+            ErrorKind::TrailingBytes => "trailing bytes",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+///A decoding error, with the byte offset in the input where it happened and the path of the value being
+///decoded.
+///
+///The path is a breadcrumb of type and field names, added by decoders with [`Error::context`] while the
+///error is returned to the outermost decoder.
+/// # Example
+/// ```
+/// # use oscore::abi::{Decoder, ErrorKind, Source};
+/// #[derive(Decoder, Debug)]
+/// struct Task {
+///     id: u32,
+///     done: bool,
+/// }
+///
+/// let mut source = Source::new(&[1, 7, 0, 0, 0, 2]);
+/// let err = source.read::<Vec<Task>>().unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::IrregularData);
+/// assert_eq!(err.position(), 5);
+/// assert_eq!(err.to_string(), "irregular data at byte 5 in Vec[0] > Task.done");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    kind: ErrorKind,
+    position: usize,
+    path: Vec<Cow<'static, str>>,
+}
+
+impl Error {
+    ///Create an error at the byte offset of the input, use `Source::error` to get the current offset
+    pub fn new(kind: ErrorKind, position: usize) -> Self {
+        Error {
+            kind,
+            position,
+            path: Vec::new(),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    ///The byte offset in the input where the error happened
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    ///Add the name of the enclosing value to the path, called by decoders as the error is returned
+    pub fn context(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.path.push(name.into());
+        self
+    }
+
+    ///The path of the value being decoded, from the outermost value
+    pub fn path(&self) -> impl Iterator<Item = &str> {
+        self.path.iter().rev().map(|name| name.as_ref())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.position)?;
+        for (i, name) in self.path().enumerate() {
+            f.write_str(if i == 0 { " in " } else { " > " })?;
+            f.write_str(name)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
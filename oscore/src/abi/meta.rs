@@ -0,0 +1,365 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+
+pub use oscore_macros::TypeInfo;
+
+///Describe how a type is encoded, so the ABI manifest of a contract can list its layout.
+///
+///User structs and enums use `#[derive(TypeInfo)]`, which describes the layout generated by
+///`#[derive(Encoder, Decoder)]`.
+pub trait TypeInfo {
+    ///The name of the type in the manifest, such as `u32`, `Vec<String>` or the name of a struct
+    fn type_name() -> String;
+
+    ///Register the layouts of the type and of the types it contains, nothing for builtin types
+    fn register(_registry: &mut Registry) {}
+}
+
+///A field of a struct, a variant or the argument of a method. Fields of tuples are named by index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDef {
+    pub name: String,
+    pub ty: String,
+}
+
+impl FieldDef {
+    pub fn new(name: impl Into<String>, ty: impl Into<String>) -> Self {
+        FieldDef {
+            name: name.into(),
+            ty: ty.into(),
+        }
+    }
+}
+
+///A variant of an enum, encoded as its index in one byte followed by its fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantDef {
+    pub name: String,
+    pub index: u8,
+    pub fields: Vec<FieldDef>,
+}
+
+///The layout of a user type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeDef {
+    Struct {
+        name: String,
+        fields: Vec<FieldDef>,
+    },
+    Enum {
+        name: String,
+        variants: Vec<VariantDef>,
+    },
+}
+
+impl TypeDef {
+    pub fn name(&self) -> &str {
+        match self {
+            TypeDef::Struct { name, .. } | TypeDef::Enum { name, .. } => name,
+        }
+    }
+}
+
+///The layouts of the user types used by a contract, in registration order
+#[derive(Debug, Default, Clone)]
+pub struct Registry {
+    types: Vec<TypeDef>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.types.iter().any(|def| def.name() == name)
+    }
+
+    ///Add a layout, return false if a type of the same name is already registered
+    pub fn insert(&mut self, def: TypeDef) -> bool {
+        if self.contains(def.name()) {
+            return false;
+        }
+        self.types.push(def);
+        true
+    }
+
+    pub fn into_types(self) -> Vec<TypeDef> {
+        self.types
+    }
+}
+
+///A method dispatched by the contract. The return type is the type of the returned value, errors of
+///methods returning `Result` go through the panic channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodDef {
+    pub name: String,
+    pub args: Vec<FieldDef>,
+    pub ret: String,
+}
+
+///The ABI manifest of a contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractAbi {
+    pub name: String,
+    pub methods: Vec<MethodDef>,
+    pub types: Vec<TypeDef>,
+}
+
+///Implemented by `#[contract(abi)]` on native targets, to get the manifest of the contract.
+///
+///A helper binary or example of the contract crate can print it, built with the `std` feature of oscore so
+///the host imports are linked to the mock host, as `examples/abi.rs` of oscore does.
+/// # Example
+/// ```
+/// use oscore::abi::meta::Contract;
+/// use oscore::macros::contract;
+///
+/// struct Score;
+///
+/// #[contract(abi)]
+/// impl Score {
+///     pub fn add(a: u32, b: u32) -> u32 {
+///         a + b
+///     }
+/// }
+///
+/// let abi = Score::abi();
+/// assert_eq!(abi.methods[0].name, "add");
+/// assert_eq!(abi.methods[0].ret, "u32");
+/// ```
+pub trait Contract {
+    fn abi() -> ContractAbi;
+}
+
+fn write_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_fields(out: &mut String, fields: &[FieldDef]) {
+    out.push('[');
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"name\":");
+        write_str(out, &field.name);
+        out.push_str(",\"type\":");
+        write_str(out, &field.ty);
+        out.push('}');
+    }
+    out.push(']');
+}
+
+impl ContractAbi {
+    ///Serialize the manifest to JSON
+    /// # Example
+    /// ```
+    /// # use oscore::abi::meta::{ContractAbi, FieldDef, MethodDef};
+    /// let abi = ContractAbi {
+    ///     name: "Score".to_string(),
+    ///     methods: vec![MethodDef {
+    ///         name: "add".to_string(),
+    ///         args: vec![FieldDef::new("a", "u32"), FieldDef::new("b", "u32")],
+    ///         ret: "u32".to_string(),
+    ///     }],
+    ///     types: vec![],
+    /// };
+    /// assert_eq!(
+    ///     abi.to_json(),
+    ///     r#"{"name":"Score","methods":[{"name":"add","args":[{"name":"a","type":"u32"},{"name":"b","type":"u32"}],"return":"u32"}],"types":[]}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        out.push_str("{\"name\":");
+        write_str(&mut out, &self.name);
+        out.push_str(",\"methods\":[");
+        for (i, method) in self.methods.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str("{\"name\":");
+            write_str(&mut out, &method.name);
+            out.push_str(",\"args\":");
+            write_fields(&mut out, &method.args);
+            out.push_str(",\"return\":");
+            write_str(&mut out, &method.ret);
+            out.push('}');
+        }
+        out.push_str("],\"types\":[");
+        for (i, def) in self.types.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            match def {
+                TypeDef::Struct { name, fields } => {
+                    out.push_str("{\"kind\":\"struct\",\"name\":");
+                    write_str(&mut out, name);
+                    out.push_str(",\"fields\":");
+                    write_fields(&mut out, fields);
+                }
+                TypeDef::Enum { name, variants } => {
+                    out.push_str("{\"kind\":\"enum\",\"name\":");
+                    write_str(&mut out, name);
+                    out.push_str(",\"variants\":[");
+                    for (j, variant) in variants.iter().enumerate() {
+                        if j > 0 {
+                            out.push(',');
+                        }
+                        out.push_str("{\"name\":");
+                        write_str(&mut out, &variant.name);
+                        let _ = write!(out, ",\"index\":{},\"fields\":", variant.index);
+                        write_fields(&mut out, &variant.fields);
+                        out.push('}');
+                    }
+                    out.push(']');
+                }
+            }
+            out.push('}');
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+macro_rules! impl_type_info {
+    ($($ty:ty => $name:expr),*) => {
+        $(
+            impl TypeInfo for $ty {
+                fn type_name() -> String {
+                    $name.to_string()
+                }
+            }
+        )*
+    };
+}
+
+impl_type_info!(
+    bool => "bool", u8 => "u8", u16 => "u16", u32 => "u32", u64 => "u64", u128 => "u128",
+    i8 => "i8", i16 => "i16", i32 => "i32", i64 => "i64", i128 => "i128", str => "String",
+    String => "String"
+);
+
+#[cfg(feature = "float")]
+impl_type_info!(f32 => "f32", f64 => "f64");
+
+impl<T: TypeInfo + ?Sized> TypeInfo for &T {
+    fn type_name() -> String {
+        T::type_name()
+    }
+
+    fn register(registry: &mut Registry) {
+        T::register(registry)
+    }
+}
+
+impl<T: TypeInfo> TypeInfo for [T] {
+    fn type_name() -> String {
+        format!("Vec<{}>", T::type_name())
+    }
+
+    fn register(registry: &mut Registry) {
+        T::register(registry)
+    }
+}
+
+impl<T: TypeInfo> TypeInfo for Vec<T> {
+    fn type_name() -> String {
+        format!("Vec<{}>", T::type_name())
+    }
+
+    fn register(registry: &mut Registry) {
+        T::register(registry)
+    }
+}
+
+impl<K: TypeInfo, V: TypeInfo> TypeInfo for BTreeMap<K, V> {
+    fn type_name() -> String {
+        format!("Map<{}, {}>", K::type_name(), V::type_name())
+    }
+
+    fn register(registry: &mut Registry) {
+        K::register(registry);
+        V::register(registry);
+    }
+}
+
+impl<K: TypeInfo, V: TypeInfo, S> TypeInfo for HashMap<K, V, S> {
+    fn type_name() -> String {
+        BTreeMap::<K, V>::type_name()
+    }
+
+    fn register(registry: &mut Registry) {
+        BTreeMap::<K, V>::register(registry)
+    }
+}
+
+impl<K: TypeInfo> TypeInfo for BTreeSet<K> {
+    fn type_name() -> String {
+        format!("Set<{}>", K::type_name())
+    }
+
+    fn register(registry: &mut Registry) {
+        K::register(registry)
+    }
+}
+
+impl<K: TypeInfo, S> TypeInfo for HashSet<K, S> {
+    fn type_name() -> String {
+        BTreeSet::<K>::type_name()
+    }
+
+    fn register(registry: &mut Registry) {
+        BTreeSet::<K>::register(registry)
+    }
+}
+
+impl<T: TypeInfo> TypeInfo for Option<T> {
+    fn type_name() -> String {
+        format!("Option<{}>", T::type_name())
+    }
+
+    fn register(registry: &mut Registry) {
+        T::register(registry)
+    }
+}
+
+impl<const N: usize> TypeInfo for [u8; N] {
+    fn type_name() -> String {
+        format!("[u8; {}]", N)
+    }
+}
+
+for_each_tuple! {
+    ($($item:ident)*) => {
+        impl<$($item: TypeInfo),*> TypeInfo for ($($item,)*) {
+            fn type_name() -> String {
+                let names: Vec<String> = vec![$($item::type_name()),*];
+                if names.len() == 1 {
+                    format!("({},)", names[0])
+                } else {
+                    format!("({})", names.join(", "))
+                }
+            }
+
+            fn register(_registry: &mut Registry) {
+                $($item::register(_registry);)*
+            }
+        }
+    }
+}
@@ -1,24 +1,132 @@
+/// reference:
+/// 1. https://github.com/rust-lang/rust/issues/24830
+/// 2. https://github.com/rust-lang/rust/blob/8f5b5f94dcdb9884737dfbc8efd893d1d70f0b14/src/libcore/hash/mod.rs#L239
+/// 3. https://github.com/rust-num/num/pull/89/files
+macro_rules! for_each_tuple_ {
+    ($m:ident !!) => {
+        $m! { }
+    };
+    ($m:ident !! $h:ident, $($t:ident,)*) => {
+        $m! { $h $($t)* }
+        for_each_tuple_! { $m !! $($t,)* }
+    }
+}
+macro_rules! for_each_tuple {
+    ($($m:tt)*) => {
+        macro_rules! m { $($m)* }
+        for_each_tuple_! { m !! A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, }
+    }
+}
+
 mod codec;
+mod error;
+///The meta module describes the layouts of encoded types, to generate the ABI manifest of contracts
+pub mod meta;
+///Serde support on the binary ABI, for types deriving `Serialize` and `Deserialize` instead of
+///`Encoder` and `Decoder`. Enabled by the `serde` feature.
+#[cfg(feature = "serde")]
+pub mod serde;
 mod sink;
 mod source;
+mod varuint;
+mod versioned;
 
-pub use self::sink::Sink;
-pub use self::source::Source;
-
-#[derive(Debug)]
-pub enum Error {
-    UnexpectedEOF,
-    IrregularData,
-    InvalidUtf8,
-    TypeInconsistency,
-    LengthInconsistency,
-}
+pub use self::error::{Error, ErrorKind};
+pub use self::sink::{BufferOverflow, Sink};
+pub use self::source::{DecodeLimits, Source};
+pub use self::varuint::encode_size as varuint_encode_size;
+pub use self::versioned::{decode_versioned, encode_versioned};
+pub use oscore_macros::{Decoder, Encoder};
 
+///Encode a value into a `Sink`.
+///
+///Integers are encoded in little endian, signed integers in two's complement.
+///```
+///# use oscore::abi::{Sink, Source};
+///let mut sink = Sink::new(0);
+///sink.write(-2i16);
+///assert_eq!(sink.bytes(), [0xfe, 0xff]);
+///let mut source = Source::new(sink.bytes());
+///assert_eq!(source.read::<i16>().unwrap(), -2);
+///```
+///
+///Maps and sets are encoded as their length followed by the entries in ascending order of the keys, so a
+///`HashMap` is encoded like the `BTreeMap` of the same entries. Decoders reject duplicate or unsorted keys.
+///```
+///# use oscore::abi::{ErrorKind, Sink, Source};
+///# use std::collections::{BTreeMap, HashMap};
+///let map: HashMap<u8, bool> = vec![(2, true), (1, false)].into_iter().collect();
+///let mut sink = Sink::new(0);
+///sink.write(&map);
+///assert_eq!(sink.bytes(), [2, 1, 0, 2, 1]);
+///let mut source = Source::new(sink.bytes());
+///assert_eq!(source.read::<BTreeMap<u8, bool>>().unwrap().len(), 2);
+///let mut source = Source::new(&[2, 2, 1, 1, 0]);
+///let err = source.read::<BTreeMap<u8, bool>>().unwrap_err();
+///assert_eq!(err.kind(), ErrorKind::IrregularData);
+///```
+///
+///With the `float` feature, `f32` and `f64` are encoded as the little endian bytes of their bits. NaN
+///and infinities are rejected by decoders, and encoding them panics. Prefer `types::Decimal` for amounts.
+///
+///`usize` and `isize` are deliberately not encodable: their width differs between wasm32 and native
+///targets, so the same value would be encoded differently. Use a fixed width integer instead.
+///```compile_fail
+///# use oscore::abi::Sink;
+///let mut sink = Sink::new(0);
+///sink.write(1usize);
+///```
 pub trait Encoder {
     fn encode(&self, sink: &mut Sink);
+
+    ///The number of bytes of the encoded value, to size buffers exactly. The codecs of this crate and the
+    ///derived encoders compute it without encoding; the default implementation encodes into a counting sink.
+    ///An override must be exact: derived encoders of versioned structs rely on it to write the size of
+    ///their body, and encode the body twice when it is wrong.
+    /// # Example
+    /// ```
+    /// # use oscore::abi::Encoder;
+    /// assert_eq!("abc".encoded_size(), 4);
+    /// assert_eq!(vec![1u32, 2].encoded_size(), 9);
+    /// ```
+    fn encoded_size(&self) -> usize {
+        let mut sink = Sink::counter();
+        self.encode(&mut sink);
+        sink.len()
+    }
 }
 
 #[doc(hidden)]
 pub trait Decoder<'a>: Sized {
     fn decode(source: &mut Source<'a>) -> Result<Self, Error>;
 }
+
+///Decode a value which must span the whole input, trailing bytes are rejected.
+/// # Example
+/// ```
+/// # use oscore::abi::{decode_all, ErrorKind};
+/// assert_eq!(decode_all::<u16>(&[1, 0]).unwrap(), 1);
+/// assert_eq!(decode_all::<u16>(&[1, 0, 0]).unwrap_err().kind(), ErrorKind::TrailingBytes);
+/// ```
+pub fn decode_all<'a, T: Decoder<'a>>(data: &'a [u8]) -> Result<T, Error> {
+    let mut source = Source::new(data);
+    let value = source.read()?;
+    source.finish()?;
+    Ok(value)
+}
+
+///Decode untrusted input like `decode_all`, within the default `DecodeLimits`.
+/// # Example
+/// ```
+/// # use oscore::abi::{decode_untrusted, ErrorKind};
+/// // a vec declaring u64::MAX elements
+/// let data = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+/// let err = decode_untrusted::<Vec<u8>>(&data).unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+/// ```
+pub fn decode_untrusted<'a, T: Decoder<'a>>(data: &'a [u8]) -> Result<T, Error> {
+    let mut source = Source::new(data).with_limits(DecodeLimits::default());
+    let value = source.read()?;
+    source.finish()?;
+    Ok(value)
+}
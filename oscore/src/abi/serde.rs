@@ -0,0 +1,743 @@
+//!The data model of serde is mapped onto the encoding of `Sink` and `Source`:
+//!* integers are encoded in little endian, `f32` and `f64` as the little endian bytes of their bits and
+//!  `char` as its `u32` code point. Like the codecs of the `float` feature, NaN and infinities are
+//!  rejected in both directions
+//!* strings and bytes are prefixed with their varuint length, and deserialized borrowed from the input
+//!* options are a bool followed by the value, like `Option<T>`
+//!* sequences and maps are prefixed with their varuint length, so their length must be known when
+//!  serializing. Maps are written in iteration order, use a `BTreeMap` for a canonical encoding
+//!* structs and tuples are their fields in order, without names
+//!* enum variants are their index in one byte followed by their fields
+//!
+//!The encoding is not self describing, so `deserialize_any` is not supported.
+//!
+//!The `DecodeLimits` of the source are honored: owned strings and bytes are charged to `max_alloc` by
+//!their length, and the elements of sequences and maps by their size as they are deserialized.
+//! # Example
+//! ```
+//! use oscore::abi::serde::{from_bytes, to_bytes};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Request<'a> {
+//!     method: &'a str,
+//!     rewards: Vec<f32>,
+//! }
+//!
+//! let req = Request { method: "claim", rewards: vec![1.5] };
+//! let bytes = to_bytes(&req).unwrap();
+//! assert_eq!(bytes, [5, b'c', b'l', b'a', b'i', b'm', 1, 0, 0, 0xc0, 0x3f]);
+//! assert_eq!(from_bytes::<Request>(&bytes).unwrap(), req);
+//! ```
+use super::{Error as AbiError, ErrorKind, Sink, Source};
+use ::serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use ::serde::ser::{self, Serialize};
+use std::fmt;
+
+///Errors of serializing and deserializing with serde
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    ///The input can not be decoded
+    Decode(AbiError),
+    ///A sequence or a map was serialized without its length
+    UnknownLength,
+    ///A part of the serde data model the encoding can not represent
+    Unsupported(&'static str),
+    ///An error reported by a `Serialize` or `Deserialize` implementation
+    Custom(String),
+}
+
+impl From<AbiError> for Error {
+    fn from(err: AbiError) -> Self {
+        Error::Decode(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Decode(err) => err.fmt(f),
+            Error::UnknownLength => f.write_str("length of sequence or map is unknown"),
+            Error::Unsupported(what) => write!(f, "{} is not supported", what),
+            Error::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+///Serialize a value into a new buffer
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut sink = Sink::new(64);
+    value.serialize(&mut Serializer::new(&mut sink))?;
+    Ok(sink.into())
+}
+
+///Deserialize a value which must span the whole input, like `abi::decode_all`
+pub fn from_bytes<'a, T: de::Deserialize<'a>>(data: &'a [u8]) -> Result<T, Error> {
+    let mut source = Source::new(data);
+    let value = T::deserialize(&mut Deserializer::new(&mut source))?;
+    source.finish()?;
+    Ok(value)
+}
+
+///Serialize values into a `Sink`
+pub struct Serializer<'s, 'b> {
+    sink: &'s mut Sink<'b>,
+}
+
+impl<'s, 'b> Serializer<'s, 'b> {
+    pub fn new(sink: &'s mut Sink<'b>) -> Self {
+        Serializer { sink }
+    }
+
+    fn write_variant(&mut self, index: u32) -> Result<(), Error> {
+        if index > u8::MAX as u32 {
+            return Err(Error::Unsupported("enum with more than 256 variants"));
+        }
+        self.sink.write_byte(index as u8);
+        Ok(())
+    }
+
+    fn write_len(&mut self, len: Option<usize>) -> Result<(), Error> {
+        let len = len.ok_or(Error::UnknownLength)?;
+        self.sink.write_varuint(len as u64);
+        Ok(())
+    }
+}
+
+impl<'a, 's, 'b> ser::Serializer for &'a mut Serializer<'s, 'b> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.sink.write_bool(v);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.sink.write(v);
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.sink.write(v);
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.sink.write(v);
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.sink.write(v);
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Error> {
+        self.sink.write(v);
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.sink.write_byte(v);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.sink.write_u16(v);
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.sink.write_u32(v);
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.sink.write_u64(v);
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Error> {
+        self.sink.write(v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        if !v.is_finite() {
+            return Err(Error::Unsupported("NaN or infinite float"));
+        }
+        self.sink.write_u32(v.to_bits());
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        if !v.is_finite() {
+            return Err(Error::Unsupported("NaN or infinite float"));
+        }
+        self.sink.write_u64(v.to_bits());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.sink.write_u32(v as u32);
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.sink.write(v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.sink.write(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.sink.write_bool(false);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        self.sink.write_bool(true);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Error> {
+        self.write_variant(index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.write_variant(index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, Error> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.write_variant(index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, Error> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.write_variant(index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'a, 's, 'b> ser::SerializeSeq for &'a mut Serializer<'s, 'b> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 's, 'b> ser::SerializeTuple for &'a mut Serializer<'s, 'b> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 's, 'b> ser::SerializeTupleStruct for &'a mut Serializer<'s, 'b> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 's, 'b> ser::SerializeTupleVariant for &'a mut Serializer<'s, 'b> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 's, 'b> ser::SerializeMap for &'a mut Serializer<'s, 'b> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 's, 'b> ser::SerializeStruct for &'a mut Serializer<'s, 'b> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a, 's, 'b> ser::SerializeStructVariant for &'a mut Serializer<'s, 'b> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+///Deserialize values from a `Source`, honoring its `DecodeLimits`
+pub struct Deserializer<'s, 'a> {
+    source: &'s mut Source<'a>,
+}
+
+impl<'s, 'a> Deserializer<'s, 'a> {
+    pub fn new(source: &'s mut Source<'a>) -> Self {
+        Deserializer { source }
+    }
+
+    ///Visit a nested value, one level of depth of the source
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, Error>) -> Result<T, Error> {
+        self.source.enter()?;
+        let res = f(self);
+        self.source.leave();
+        res
+    }
+
+    ///Read the length of a sequence or a map, its elements are charged as they are deserialized
+    fn read_len(&mut self) -> Result<usize, Error> {
+        Ok(self.source.read_len::<()>()?)
+    }
+
+    ///Read a float, rejecting NaN and infinities
+    fn read_float<T>(
+        &mut self,
+        read: impl FnOnce(&mut Source<'a>) -> Result<T, AbiError>,
+        is_finite: impl FnOnce(&T) -> bool,
+    ) -> Result<T, Error> {
+        let pos = self.source.position();
+        let val = read(self.source)?;
+        if is_finite(&val) {
+            Ok(val)
+        } else {
+            Err(AbiError::new(ErrorKind::IrregularData, pos).into())
+        }
+    }
+}
+
+impl<'de, 's, 'a: 'de> de::Deserializer<'de> for &mut Deserializer<'s, 'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("deserialize_any"))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(self.source.read_bool()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i8(self.source.read()?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i16(self.source.read()?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i32(self.source.read()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(self.source.read()?)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i128(self.source.read()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u8(self.source.read_byte()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u16(self.source.read_u16()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(self.source.read_u32()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(self.source.read_u64()?)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u128(self.source.read_u128()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let val = self.read_float(
+            |source| source.read_u32().map(f32::from_bits),
+            |val| val.is_finite(),
+        )?;
+        visitor.visit_f32(val)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let val = self.read_float(
+            |source| source.read_u64().map(f64::from_bits),
+            |val| val.is_finite(),
+        )?;
+        visitor.visit_f64(val)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let pos = self.source.position();
+        let c = std::char::from_u32(self.source.read_u32()?)
+            .ok_or_else(|| AbiError::new(ErrorKind::IrregularData, pos))?;
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(self.source.read()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let s: &str = self.source.read()?;
+        self.source.alloc(s.len())?;
+        visitor.visit_borrowed_str(s)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_bytes(self.source.read_bytes()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let bytes = self.source.read_bytes()?;
+        self.source.alloc(bytes.len())?;
+        visitor.visit_borrowed_bytes(bytes)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.source.read_bool()? {
+            self.nested(|de| visitor.visit_some(de))
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.nested(|de| visitor.visit_newtype_struct(de))
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let len = self.read_len()?;
+        self.nested(|de| visitor.visit_seq(Access::collection(de, len)))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.nested(|de| visitor.visit_seq(Access::fields(de, len)))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let len = self.read_len()?;
+        self.nested(|de| visitor.visit_map(Access::collection(de, len)))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.nested(|de| visitor.visit_enum(de))
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("deserialize_identifier"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("deserialize_ignored_any"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+///The elements of a sequence, a tuple or a map of known length
+struct Access<'d, 's, 'a> {
+    de: &'d mut Deserializer<'s, 'a>,
+    len: usize,
+    // whether the elements are allocated by the collection, unlike the fields of tuples and structs
+    charge: bool,
+}
+
+impl<'d, 's, 'a> Access<'d, 's, 'a> {
+    fn collection(de: &'d mut Deserializer<'s, 'a>, len: usize) -> Self {
+        Access {
+            de,
+            len,
+            charge: true,
+        }
+    }
+
+    fn fields(de: &'d mut Deserializer<'s, 'a>, len: usize) -> Self {
+        Access {
+            de,
+            len,
+            charge: false,
+        }
+    }
+
+    ///Deserialize an element, charging its size to `max_alloc` for collections
+    fn element<'de, T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, Error>
+    where
+        'a: 'de,
+    {
+        if self.charge {
+            self.de.source.alloc(std::mem::size_of::<T::Value>())?;
+        }
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+impl<'de, 'd, 's, 'a: 'de> de::SeqAccess<'de> for Access<'d, 's, 'a> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        self.element(seed).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de, 'd, 's, 'a: 'de> de::MapAccess<'de> for Access<'d, 's, 'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        self.element(seed).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        self.element(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de, 's, 'a: 'de> de::EnumAccess<'de> for &mut Deserializer<'s, 'a> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let index = self.source.read_byte()? as u32;
+        let value = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, 's, 'a: 'de> de::VariantAccess<'de> for &mut Deserializer<'s, 'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(Access::fields(self, len))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_seq(Access::fields(self, fields.len()))
+    }
+}
@@ -1,14 +1,46 @@
-use super::Encoder;
+use super::{varuint, Encoder};
+use std::cmp;
+use std::fmt;
+
+///The buffer of a `Sink` was too small for the encoded data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferOverflow {
+    ///The number of bytes written to the sink, larger than the buffer
+    pub needed: usize,
+    pub capacity: usize,
+}
+
+impl fmt::Display for BufferOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "buffer overflow: {} bytes needed, capacity is {}",
+            self.needed, self.capacity
+        )
+    }
+}
+
+impl std::error::Error for BufferOverflow {}
+
+enum Buffer<'a> {
+    Owned(Vec<u8>),
+    // bytes past the end of the slice are counted but dropped
+    Slice { buf: &'a mut [u8], len: usize },
+    // only counts the bytes, to compute encoded sizes
+    Counter(usize),
+}
 
 ///Encoding different types of data into byte array.
-pub struct Sink {
-    buf: Vec<u8>,
+///
+///A sink owns a growable buffer, or writes into a caller-provided buffer without allocating with
+///`Sink::from_slice`.
+pub struct Sink<'a> {
+    buf: Buffer<'a>,
 }
 
-impl Sink {
+impl<'a> Sink<'a> {
     ///Create a new sink entity, Specify initial capacity.
     ///For indefinite length parameters, the length of the parameter will be serialized first, and then the content of the parameter will be serialized.
-
     ///
     ///# Example
     ///```no_run
@@ -19,7 +51,84 @@ impl Sink {
     ///```
     pub fn new(cap: usize) -> Self {
         Sink {
-            buf: Vec::with_capacity(cap),
+            buf: Buffer::Owned(Vec::with_capacity(cap)),
+        }
+    }
+
+    ///Create a sink with exactly the capacity needed to encode `val`, which is then written
+    /// # Example
+    /// ```
+    /// # use oscore::abi::Sink;
+    /// let sink = Sink::with_value(&("transfer", 100u64));
+    /// assert_eq!(sink.bytes().len(), 17);
+    /// ```
+    pub fn with_value<T: Encoder>(val: &T) -> Self {
+        let mut sink = Sink::new(val.encoded_size());
+        sink.write(val);
+        sink
+    }
+
+    ///Create a sink writing into `buf`, such as a stack array, without allocating. Writing past the end of
+    ///the buffer does not panic: the overflow is reported by `finish`, and `bytes` and `into` panic on an
+    ///overflowed sink rather than return a truncated encoding.
+    /// # Example
+    /// ```
+    /// # use oscore::abi::Sink;
+    /// let mut buf = [0u8; 4];
+    /// let mut sink = Sink::from_slice(&mut buf);
+    /// sink.write(7u16);
+    /// assert_eq!(sink.bytes(), [7, 0]);
+    /// assert_eq!(sink.finish(), Ok(2));
+    ///
+    /// let mut sink = Sink::from_slice(&mut buf);
+    /// sink.write("hello");
+    /// assert_eq!(sink.finish().unwrap_err().needed, 6);
+    /// ```
+    pub fn from_slice(buf: &'a mut [u8]) -> Self {
+        Sink {
+            buf: Buffer::Slice { buf, len: 0 },
+        }
+    }
+
+    ///A sink which only counts the bytes written
+    pub(crate) fn counter() -> Self {
+        Sink {
+            buf: Buffer::Counter(0),
+        }
+    }
+
+    ///The number of bytes written, including the bytes which overflowed the buffer
+    pub fn len(&self) -> usize {
+        match &self.buf {
+            Buffer::Owned(buf) => buf.len(),
+            Buffer::Slice { len, .. } => *len,
+            Buffer::Counter(len) => *len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///Drop the bytes written after the first `len` bytes
+    pub(crate) fn truncate(&mut self, len: usize) {
+        match &mut self.buf {
+            Buffer::Owned(buf) => buf.truncate(len),
+            Buffer::Slice { len: written, .. } | Buffer::Counter(written) => {
+                *written = cmp::min(*written, len)
+            }
+        }
+    }
+
+    ///Check that the encoded data fit in the buffer and return its length. Sinks created by `new` never
+    ///overflow.
+    pub fn finish(self) -> Result<usize, BufferOverflow> {
+        match self.buf {
+            Buffer::Slice { buf, len } if len > buf.len() => Err(BufferOverflow {
+                needed: len,
+                capacity: buf.len(),
+            }),
+            _ => Ok(self.len()),
         }
     }
 
@@ -29,14 +138,14 @@ impl Sink {
     /// # use oscore::abi::Sink;
     ///   let mut sink = Sink::new(0);
     ///   sink.write("123");
-    ///   sink.write(123 as U128);
+    ///   sink.write(123 as u128);
     ///```
     pub fn write<T: Encoder>(&mut self, val: T) {
         val.encode(self)
     }
 
     pub(crate) fn write_byte(&mut self, b: u8) {
-        self.buf.push(b)
+        self.write_bytes(&[b])
     }
 
     pub(crate) fn write_bool(&mut self, b: bool) {
@@ -48,7 +157,17 @@ impl Sink {
     }
 
     pub(crate) fn write_bytes(&mut self, data: &[u8]) {
-        self.buf.extend_from_slice(data)
+        match &mut self.buf {
+            Buffer::Owned(buf) => buf.extend_from_slice(data),
+            Buffer::Slice { buf, len } => {
+                let end = len.saturating_add(data.len());
+                if end <= buf.len() {
+                    buf[*len..end].copy_from_slice(data);
+                }
+                *len = end;
+            }
+            Buffer::Counter(len) => *len = len.saturating_add(data.len()),
+        }
     }
 
     pub(crate) fn write_u16(&mut self, val: u16) {
@@ -63,25 +182,40 @@ impl Sink {
         self.write_bytes(&val.to_le_bytes())
     }
 
-    pub(crate) fn write_varuint(&mut self, val: u64) {
-        if val < 0xFD {
-            self.write_byte(val as u8);
-        } else if val < 0xFFFF {
-            self.write_byte(0xFD);
-            self.write_u16(val as u16);
-        } else if val <= 0xFFFF_FFFF {
-            self.write_byte(0xFE);
-            self.write_u32(val as u32);
-        } else {
-            self.write_byte(0xFF);
-            self.write_u64(val);
+    ///Write a length or an integer in the canonical varuint encoding, the counterpart of
+    ///`Source::read_varuint`.
+    /// # Example
+    /// ```
+    /// # use oscore::abi::Sink;
+    /// let mut sink = Sink::new(0);
+    /// sink.write_varuint(0xFC);
+    /// sink.write_varuint(0xFFFF);
+    /// sink.write_varuint(0x1_0000);
+    /// assert_eq!(sink.bytes(), [0xFC, 0xFD, 0xFF, 0xFF, 0xFE, 0, 0, 1, 0]);
+    /// ```
+    pub fn write_varuint(&mut self, val: u64) {
+        match varuint::encode_size(val) {
+            1 => self.write_byte(val as u8),
+            3 => {
+                self.write_byte(varuint::TAG_U16);
+                self.write_u16(val as u16);
+            }
+            5 => {
+                self.write_byte(varuint::TAG_U32);
+                self.write_u32(val as u32);
+            }
+            _ => {
+                self.write_byte(varuint::TAG_U64);
+                self.write_u64(val);
+            }
         }
     }
 
     ///Used to get the serialized result in bytearray format
+    /// # Panics
+    /// If a sink created by `from_slice` overflowed its buffer, which `finish` reports without panicking.
     /// # Example
     /// ```
-    /// #![feature(proc_macro_hygiene)]
     /// use oscore::macros::base58;
     /// use oscore::types::Address;
     /// use oscore::abi::Sink;
@@ -91,10 +225,25 @@ impl Sink {
     /// assert_eq!(sink.into(), [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1].to_vec())
     /// ```
     pub fn bytes(&self) -> &[u8] {
-        &self.buf
+        match &self.buf {
+            Buffer::Owned(buf) => buf,
+            Buffer::Slice { buf, len } if *len > buf.len() => panic!(
+                "{}",
+                BufferOverflow {
+                    needed: *len,
+                    capacity: buf.len(),
+                }
+            ),
+            Buffer::Slice { buf, len } => &buf[..*len],
+            Buffer::Counter(_) => &[],
+        }
     }
 
+    ///The serialized result as a vector, panic like `bytes` if the sink overflowed
     pub fn into(self) -> Vec<u8> {
-        self.buf
+        match self.buf {
+            Buffer::Owned(buf) => buf,
+            _ => self.bytes().to_vec(),
+        }
     }
 }
@@ -1,16 +1,54 @@
-use super::Decoder;
-use super::Error;
+use super::{varuint, Decoder};
+use super::{Error, ErrorKind};
 use std::convert::TryInto;
 
-pub(crate) fn varuint_encode_size(val: u64) -> usize {
-    if val < 0xfd {
-        1
-    } else if val <= 0xffff {
-        3
-    } else if val <= 0xFFFF_FFFF {
-        5
-    } else {
-        9
+///Resource limits of decoding, to reject untrusted input which would exhaust the memory or the stack
+///before it is allocated.
+///
+///Every `Source::read` is one level of depth, collections check their declared length against
+///`max_len`, and the memory allocated by decoders of a source is added up against `max_alloc`.
+///
+///`Source::new` has no limits, so values already stored by the contract can always be read back. The
+///default limits apply to untrusted input: set them with `Source::with_limits` or use
+///`abi::decode_untrusted`. The `invoke` entry point generated by `#[contract]` decodes its input with them.
+/// # Example
+/// ```
+/// # use oscore::abi::{DecodeLimits, ErrorKind, Sink, Source};
+/// let mut sink = Sink::new(0);
+/// sink.write(vec![1u32, 2, 3]);
+/// let limits = DecodeLimits { max_len: 2, ..DecodeLimits::default() };
+/// let mut source = Source::new(sink.bytes()).with_limits(limits);
+/// let err = source.read::<Vec<u32>>().unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    ///The maximum nesting of values
+    pub max_depth: u32,
+    ///The maximum number of elements of a collection
+    pub max_len: u64,
+    ///The maximum number of bytes allocated by the decoded values
+    pub max_alloc: usize,
+}
+
+impl DecodeLimits {
+    ///No limits, for trusted input only
+    pub const fn unlimited() -> Self {
+        DecodeLimits {
+            max_depth: u32::MAX,
+            max_len: u64::MAX,
+            max_alloc: usize::MAX,
+        }
+    }
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        DecodeLimits {
+            max_depth: 64,
+            max_len: 1 << 20,
+            max_alloc: 16 << 20,
+        }
     }
 }
 
@@ -18,10 +56,15 @@ pub(crate) fn varuint_encode_size(val: u64) -> usize {
 pub struct Source<'a> {
     buf: &'a [u8],
     pos: usize,
+    // offset of `buf` in the whole input, for sub-sources
+    base: usize,
+    limits: DecodeLimits,
+    depth: u32,
+    alloc: usize,
 }
 
 impl<'a> Source<'a> {
-    ///Create a new source instance
+    ///Create a new source instance, without decoding limits
     /// # Example
     /// ```
     /// # use oscore::abi::{Source, Sink};
@@ -32,12 +75,74 @@ impl<'a> Source<'a> {
     ///   assert_eq!(res, "123");
     /// ```
     pub fn new(data: &'a [u8]) -> Self {
-        Self { buf: data, pos: 0 }
+        Self {
+            buf: data,
+            pos: 0,
+            base: 0,
+            limits: DecodeLimits::unlimited(),
+            depth: 0,
+            alloc: 0,
+        }
+    }
+
+    ///Set the limits of decoding, for untrusted input
+    pub fn with_limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+
+    ///The byte offset of the next byte to read, in the whole input for sub-sources
+    pub fn position(&self) -> usize {
+        self.base + self.pos
+    }
+
+    ///The number of bytes left to read
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    ///Check that the whole input was read, to reject trailing bytes after the decoded values.
+    /// # Example
+    /// ```
+    /// # use oscore::abi::{ErrorKind, Source};
+    /// let mut source = Source::new(&[1, 0, 2]);
+    /// assert_eq!(source.read::<u16>().unwrap(), 1);
+    /// assert_eq!(source.remaining(), 1);
+    /// assert_eq!(source.finish().unwrap_err().kind(), ErrorKind::TrailingBytes);
+    /// ```
+    pub fn finish(&self) -> Result<(), Error> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error(ErrorKind::TrailingBytes))
+        }
+    }
+
+    ///Create an error at the current byte offset. Decoders rejecting data they already read should use
+    ///`Error::new` with the offset where the value starts.
+    /// # Example
+    /// ```
+    /// # use oscore::abi::{ErrorKind, Source};
+    /// let mut source = Source::new(&[1, 2]);
+    /// source.read_byte().unwrap();
+    /// let err = source.error(ErrorKind::IrregularData);
+    /// assert_eq!(err.position(), 1);
+    /// ```
+    pub fn error(&self, kind: ErrorKind) -> Error {
+        Error::new(kind, self.position())
     }
 
     pub(crate) fn next_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
         if self.buf.len() - self.pos < len {
-            Err(Error::UnexpectedEOF)
+            Err(self.error(ErrorKind::UnexpectedEOF))
         } else {
             let bytes = &self.buf[self.pos..self.pos + len];
             self.pos += len;
@@ -73,7 +178,115 @@ impl<'a> Source<'a> {
     ///   assert_eq!(res2, 123u128);
     /// ```
     pub fn read<T: Decoder<'a>>(&mut self) -> Result<T, Error> {
-        T::decode(self)
+        self.enter()?;
+        let res = T::decode(self);
+        self.leave();
+        res
+    }
+
+    ///Enter a nested value, checking the depth against `max_depth`
+    pub(crate) fn enter(&mut self) -> Result<(), Error> {
+        if self.depth >= self.limits.max_depth {
+            return Err(self.error(ErrorKind::LimitExceeded));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    ///Read the length of a collection of `T`, checking it against `max_len` and accounting the memory of
+    ///its elements.
+    /// # Example
+    /// ```
+    /// # use oscore::abi::{Sink, Source};
+    /// let mut sink = Sink::new(0);
+    /// sink.write(vec![1u16, 2]);
+    /// let mut source = Source::new(sink.bytes());
+    /// assert_eq!(source.read_len::<u16>().unwrap(), 2);
+    /// ```
+    pub fn read_len<T>(&mut self) -> Result<usize, Error> {
+        let pos = self.position();
+        let len = self.read_varuint()?;
+        if len > self.limits.max_len || len > usize::MAX as u64 {
+            return Err(Error::new(ErrorKind::LimitExceeded, pos));
+        }
+        let len = len as usize;
+        let size = len
+            .checked_mul(std::mem::size_of::<T>())
+            .ok_or_else(|| Error::new(ErrorKind::LimitExceeded, pos))?;
+        self.alloc(size)
+            .map_err(|_| Error::new(ErrorKind::LimitExceeded, pos))?;
+        Ok(len)
+    }
+
+    ///Account bytes allocated by a decoder against `max_alloc`
+    pub fn alloc(&mut self, size: usize) -> Result<(), Error> {
+        match self.alloc.checked_add(size) {
+            Some(total) if total <= self.limits.max_alloc => {
+                self.alloc = total;
+                Ok(())
+            }
+            _ => Err(self.error(ErrorKind::LimitExceeded)),
+        }
+    }
+
+    ///Decode a value without advancing
+    /// # Example
+    /// ```
+    /// # use oscore::abi::Source;
+    /// let mut source = Source::new(&[1, 0, 2]);
+    /// assert_eq!(source.peek::<u16>().unwrap(), 1);
+    /// assert_eq!(source.read::<u16>().unwrap(), 1);
+    /// ```
+    pub fn peek<T: Decoder<'a>>(&mut self) -> Result<T, Error> {
+        let (pos, alloc) = (self.pos, self.alloc);
+        let res = self.read();
+        self.pos = pos;
+        self.alloc = alloc;
+        res
+    }
+
+    ///Decode a region prefixed with its varuint length with a child source, to decode the region without
+    ///reading past it, or to skip it. The child shares the limits, depth and allocation budget of its
+    ///parent, and the positions of its errors are offsets in the whole input. Bytes left in the region
+    ///are skipped.
+    /// # Example
+    /// ```
+    /// # use oscore::abi::{ErrorKind, Source};
+    /// let mut source = Source::new(&[2, 7, 0, 9]);
+    /// let err = source
+    ///     .with_sub_source(|sub| {
+    ///         assert_eq!(sub.read::<u8>().unwrap(), 7);
+    ///         sub.read::<u16>()
+    ///     })
+    ///     .unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::UnexpectedEOF);
+    /// assert_eq!(err.position(), 2);
+    ///
+    /// let mut source = Source::new(&[2, 7, 0, 9]);
+    /// assert_eq!(source.with_sub_source(|sub| sub.read::<u8>()).unwrap(), 7);
+    /// assert_eq!(source.read_byte().unwrap(), 9);
+    /// ```
+    pub fn with_sub_source<T, F>(&mut self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Source<'a>) -> Result<T, Error>,
+    {
+        let data = self.read_bytes()?;
+        let mut sub = Source {
+            buf: data,
+            pos: 0,
+            base: self.position() - data.len(),
+            limits: self.limits,
+            depth: self.depth,
+            alloc: self.alloc,
+        };
+        let res = f(&mut sub)?;
+        // the nesting of the child is balanced when it returns, its allocations are the parent's
+        self.alloc = sub.alloc;
+        Ok(res)
     }
 
     pub(crate) fn read_into(&mut self, buf: &mut [u8]) -> Result<(), Error> {
@@ -92,12 +305,25 @@ impl<'a> Source<'a> {
     ///   assert_eq!(res, b'1');
     /// ```
     pub fn read_byte(&mut self) -> Result<u8, Error> {
+        let b = self.peek_byte()?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    ///Read the next byte without advancing, such as the tag of an enum
+    /// # Example
+    /// ```
+    /// # use oscore::abi::Source;
+    /// let mut source = Source::new(&[3]);
+    /// assert_eq!(source.peek_byte().unwrap(), 3);
+    /// assert_eq!(source.read_byte().unwrap(), 3);
+    /// assert!(source.peek_byte().is_err());
+    /// ```
+    pub fn peek_byte(&self) -> Result<u8, Error> {
         if self.pos >= self.buf.len() {
-            Err(Error::UnexpectedEOF)
+            Err(self.error(ErrorKind::UnexpectedEOF))
         } else {
-            let b = self.buf[self.pos];
-            self.pos += 1;
-            Ok(b)
+            Ok(self.buf[self.pos])
         }
     }
 
@@ -112,17 +338,18 @@ impl<'a> Source<'a> {
     /// assert_eq!(res, true);
     /// ```
     pub fn read_bool(&mut self) -> Result<bool, Error> {
+        let pos = self.position();
         match self.read_byte()? {
             0 => Ok(false),
             1 => Ok(true),
-            _ => Err(Error::IrregularData),
+            _ => Err(Error::new(ErrorKind::IrregularData, pos)),
         }
     }
 
     #[allow(unused)]
     pub fn skip(&mut self, n: usize) -> Result<(), Error> {
         if self.buf.len() - self.pos < n {
-            Err(Error::UnexpectedEOF)
+            Err(self.error(ErrorKind::UnexpectedEOF))
         } else {
             self.pos += n;
             Ok(())
@@ -134,12 +361,12 @@ impl<'a> Source<'a> {
     /// ```
     /// # use oscore::abi::{Source, Sink};
     ///   let mut sink = Sink::new(0);
-    ///   sink.write(123 as U128);
+    ///   sink.write(123 as u128);
     ///   let mut source = Source::new(sink.bytes());
     ///   source.read_byte();//Read a byte of data here
     ///   source.backup(1);//Back one byte
     ///   let res = source.read_u128().unwrap_or_default();
-    ///   assert_eq!(res, 123 as U128);
+    ///   assert_eq!(res, 123 as u128);
     /// ```
     #[allow(unused)]
     pub fn backup(&mut self, n: usize) {
@@ -192,10 +419,10 @@ impl<'a> Source<'a> {
     /// ```
     /// # use oscore::abi::{Source, Sink};
     ///   let mut sink = Sink::new(0);
-    ///   sink.write(123 as U128);
+    ///   sink.write(123 as u128);
     ///   let mut source = Source::new(sink.bytes());
     ///   let res = source.read_u128().unwrap_or_default();
-    ///   assert_eq!(res, 123 as U128);
+    ///   assert_eq!(res, 123 as u128);
     /// ```
     pub fn read_u128(&mut self) -> Result<u128, Error> {
         Ok(u128::from_le_bytes(
@@ -203,19 +430,27 @@ impl<'a> Source<'a> {
         ))
     }
 
+    ///Read a varuint, rejecting values not in the canonical encoding
+    /// # Example
+    /// ```
+    /// # use oscore::abi::{ErrorKind, Source};
+    /// let mut source = Source::new(&[0xFD, 0xFF, 0xFF]);
+    /// assert_eq!(source.read_varuint().unwrap(), 0xFFFF);
+    /// let mut source = Source::new(&[0xFD, 0xFC, 0]);
+    /// assert_eq!(source.read_varuint().unwrap_err().kind(), ErrorKind::IrregularData);
+    /// ```
     pub fn read_varuint(&mut self) -> Result<u64, Error> {
-        match self.read_byte()? {
-            0xFD => self.read_u16().map(|v| (3, v as u64)),
-            0xFE => self.read_u32().map(|v| (5, v as u64)),
-            0xFF => self.read_u64().map(|v| (9, v)),
-            val => Ok((1, val as u64)),
+        let pos = self.position();
+        let (len, val) = match self.read_byte()? {
+            varuint::TAG_U16 => (3, self.read_u16()? as u64),
+            varuint::TAG_U32 => (5, self.read_u32()? as u64),
+            varuint::TAG_U64 => (9, self.read_u64()?),
+            val => (1, val as u64),
+        };
+        if len == varuint::encode_size(val) {
+            Ok(val)
+        } else {
+            Err(Error::new(ErrorKind::IrregularData, pos))
         }
-        .and_then(|(len, val)| {
-            if len == varuint_encode_size(val) {
-                Ok(val)
-            } else {
-                Err(Error::IrregularData)
-            }
-        })
     }
 }
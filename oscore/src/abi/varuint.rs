@@ -0,0 +1,32 @@
+//!The canonical encoding of variable length unsigned integers, used for the lengths of bytes, strings
+//!and collections:
+//!* below `0xFD`: the value in one byte
+//!* up to `0xFFFF`: `0xFD` followed by the value as `u16`
+//!* up to `0xFFFF_FFFF`: `0xFE` followed by the value as `u32`
+//!* otherwise: `0xFF` followed by the value as `u64`
+//!
+//!Every value has exactly one encoding, decoders reject a value encoded in a longer form than needed.
+
+pub(crate) const TAG_U16: u8 = 0xFD;
+pub(crate) const TAG_U32: u8 = 0xFE;
+pub(crate) const TAG_U64: u8 = 0xFF;
+
+///The number of bytes of the canonical encoding of `val`, exported as `abi::varuint_encode_size`
+/// # Example
+/// ```
+/// # use oscore::abi::varuint_encode_size;
+/// assert_eq!(varuint_encode_size(0xFC), 1);
+/// assert_eq!(varuint_encode_size(0xFFFF), 3);
+/// assert_eq!(varuint_encode_size(0x1_0000), 5);
+/// ```
+pub fn encode_size(val: u64) -> usize {
+    if val < TAG_U16 as u64 {
+        1
+    } else if val <= 0xFFFF {
+        3
+    } else if val <= 0xFFFF_FFFF {
+        5
+    } else {
+        9
+    }
+}
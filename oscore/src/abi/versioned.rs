@@ -0,0 +1,83 @@
+use super::{Error, Sink, Source};
+
+///Write a versioned envelope: the version in one byte followed by the body prefixed with its varuint
+///length, so decoders of other versions can skip the fields they don't know. With the `body_size` given,
+///the body is written directly into `sink`, otherwise it is first encoded into a temporary buffer.
+///`body_size` must be exact, like `Encoder::encoded_size`: if it is not, the envelope is still written
+///correctly but the body is encoded twice.
+///
+///`#[derive(Encoder, Decoder)]` writes structs with `#[abi(version = N)]` in an envelope, and fields added
+///in later versions are marked with `#[abi(since = N)]`: they are decoded with their `Default` value from
+///older versions, and the unknown trailing fields of newer versions are skipped. The fields must be in the
+///order of their `since`, after the fields without `since`, or older decoders would misread the body:
+/// ```compile_fail
+/// # use oscore::abi::{Decoder, Encoder};
+/// #[derive(Encoder, Decoder)]
+/// #[abi(version = 2)]
+/// struct Profile {
+///     name: String,
+///     #[abi(since = 2)]
+///     score: u32,
+///     level: u8,
+/// }
+/// ```
+/// # Example
+/// ```
+/// # use oscore::abi::{Decoder, Encoder, Sink, Source};
+/// #[derive(Encoder, Decoder, Debug, PartialEq)]
+/// #[abi(version = 1)]
+/// struct ProfileV1 {
+///     name: String,
+/// }
+///
+/// #[derive(Encoder, Decoder, Debug, PartialEq)]
+/// #[abi(version = 2)]
+/// struct Profile {
+///     name: String,
+///     #[abi(since = 2)]
+///     score: u32,
+/// }
+///
+/// let mut sink = Sink::new(0);
+/// sink.write(ProfileV1 { name: "ok".to_string() });
+/// assert_eq!(sink.bytes(), [1, 3, 2, b'o', b'k']);
+/// let profile: Profile = Source::new(sink.bytes()).read().unwrap();
+/// assert_eq!(profile, Profile { name: "ok".to_string(), score: 0 });
+///
+/// let mut sink = Sink::new(0);
+/// sink.write(Profile { name: "ok".to_string(), score: 7 });
+/// let old: ProfileV1 = Source::new(sink.bytes()).read().unwrap();
+/// assert_eq!(old, ProfileV1 { name: "ok".to_string() });
+/// ```
+pub fn encode_versioned<F: Fn(&mut Sink)>(
+    sink: &mut Sink,
+    version: u8,
+    body_size: Option<usize>,
+    body: F,
+) {
+    sink.write_byte(version);
+    if let Some(size) = body_size {
+        let start = sink.len();
+        sink.write_varuint(size as u64);
+        let body_start = sink.len();
+        body(sink);
+        if sink.len() - body_start == size {
+            return;
+        }
+        // the size was wrong, an `encoded_size` of a field is not exact: encode the body again
+        sink.truncate(start);
+    }
+    let mut buf = Sink::new(0);
+    body(&mut buf);
+    sink.write(buf.bytes());
+}
+
+///Decode a versioned envelope written by `encode_versioned`, `body` decodes the body given its version.
+///The body may have trailing bytes of fields added in newer versions, which are skipped.
+pub fn decode_versioned<'a, T, F>(source: &mut Source<'a>, body: F) -> Result<T, Error>
+where
+    F: FnOnce(u8, &mut Source<'a>) -> Result<T, Error>,
+{
+    let version = source.read_byte()?;
+    source.with_sub_source(|source| body(version, source))
+}
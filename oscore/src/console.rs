@@ -0,0 +1,120 @@
+use crate::runtime;
+use std::fmt;
+
+///The level of a log line, from the most to the least severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+}
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+///The most verbose level compiled into the contract, chosen by the `max_level_*` features.
+///
+///`0` means logging is off. When several of the features are enabled, the most restrictive one wins.
+pub const MAX_LEVEL: u8 = if cfg!(feature = "max_level_off") {
+    0
+} else if cfg!(feature = "max_level_error") {
+    Level::Error as u8
+} else if cfg!(feature = "max_level_warn") {
+    Level::Warn as u8
+} else if cfg!(feature = "max_level_info") {
+    Level::Info as u8
+} else {
+    Level::Debug as u8
+};
+
+#[doc(hidden)]
+pub fn __log(level: Level, module: &str, args: fmt::Arguments) {
+    runtime::debug(&format!("[{}] {}: {}", level, module, args));
+}
+
+///Log a line at the level, tagged with the level and the module path.
+///
+///The line is not compiled into the contract if the level is above [`MAX_LEVEL`].
+/// # Example
+/// ```no_run
+/// # use oscore::console::{self, Level};
+/// console::log!(Level::Info, "score: {}", 90);
+/// ```
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {{
+        let level: $crate::console::Level = $level;
+        if level as u8 <= $crate::console::MAX_LEVEL {
+            $crate::console::__log(level, module_path!(), format_args!($($arg)+));
+        }
+    }};
+}
+
+///Log a line at the error level.
+/// # Example
+/// ```no_run
+/// # use oscore::console;
+/// console::error!("invalid param: {}", "sig");
+/// ```
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::console::Level::Error, $($arg)+)
+    };
+}
+
+///Log a line at the warn level.
+/// # Example
+/// ```no_run
+/// # use oscore::console;
+/// console::warn!("no task since {} days", 90);
+/// ```
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::console::Level::Warn, $($arg)+)
+    };
+}
+
+///Log a line at the info level.
+/// # Example
+/// ```no_run
+/// # use oscore::console;
+/// console::info!("score: {}", 90);
+/// ```
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::console::Level::Info, $($arg)+)
+    };
+}
+
+///Log a line at the debug level.
+/// # Example
+/// ```no_run
+/// # use oscore::console;
+/// console::debug!("contribution: {}, activeness: {}", 80, 70);
+/// ```
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        $crate::log!($crate::console::Level::Debug, $($arg)+)
+    };
+}
+
+pub use crate::{debug, error, info, log, warn};
@@ -0,0 +1,88 @@
+use crate::abi::{Sink, Source};
+
+///The first byte of an encoded error. Plain panic messages are text and do not start with it.
+pub const ERROR_MARKER: u8 = 0;
+
+///An error which aborts the contract with a numeric code, so callers can match on the code instead of
+///parsing the message.
+///
+///The error is returned through the panic channel in a stable binary layout: [`ERROR_MARKER`], the code
+///as `u32` in little endian, then the message encoded as `&str` by `abi::Sink`.
+/// # Example
+/// ```no_run
+/// # use oscore::error::ContractError;
+/// # use oscore::runtime;
+/// enum ScoreError {
+///     InvalidParam,
+///     NoPermission,
+/// }
+///
+/// impl ContractError for ScoreError {
+///     fn code(&self) -> u32 {
+///         match self {
+///             ScoreError::InvalidParam => 1,
+///             ScoreError::NoPermission => 2,
+///         }
+///     }
+///
+///     fn message(&self) -> String {
+///         match self {
+///             ScoreError::InvalidParam => "invalid param".to_string(),
+///             ScoreError::NoPermission => "no permission".to_string(),
+///         }
+///     }
+/// }
+///
+/// let res: Result<u32, ScoreError> = Err(ScoreError::InvalidParam);
+/// runtime::ret_result(res);
+/// ```
+pub trait ContractError {
+    fn code(&self) -> u32;
+    fn message(&self) -> String;
+}
+
+///A decoded contract error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorInfo {
+    pub code: u32,
+    pub message: String,
+}
+
+impl ContractError for ErrorInfo {
+    fn code(&self) -> u32 {
+        self.code
+    }
+
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+///Encode the error in the layout of [`ContractError`]
+/// # Example
+/// ```
+/// # use oscore::error::{self, ErrorInfo};
+/// let err = ErrorInfo { code: 1, message: "invalid param".to_string() };
+/// let data = error::encode_error(&err);
+/// assert_eq!(error::decode_error(&data), Some(err));
+/// ```
+pub fn encode_error<E: ContractError + ?Sized>(err: &E) -> Vec<u8> {
+    let message = err.message();
+    let mut sink = Sink::new(message.len() + 8);
+    sink.write(ERROR_MARKER);
+    sink.write(err.code());
+    sink.write(message);
+    sink.into()
+}
+
+///Decode an error encoded by [`encode_error`], `None` if the data is a plain panic message or malformed
+pub fn decode_error(data: &[u8]) -> Option<ErrorInfo> {
+    let mut source = Source::new(data);
+    if source.read_byte().ok()? != ERROR_MARKER {
+        return None;
+    }
+    let code = source.read_u32().ok()?;
+    let message = source.read().ok()?;
+    source.finish().ok()?;
+    Some(ErrorInfo { code, message })
+}
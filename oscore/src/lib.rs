@@ -3,9 +3,14 @@
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 /// Overrides the default panic_fmt
+// `PanicInfo` is the deprecated alias of `PanicHookInfo` since Rust 1.81, which older toolchains lack
+#[allow(deprecated)]
 pub fn panic_handler(info: &std::panic::PanicInfo) {
     let panic_msg = format!("{}", info);
-    runtime::panic(&panic_msg)
+    #[cfg(not(feature = "std"))]
+    runtime::panic(&panic_msg);
+    #[cfg(feature = "std")]
+    mock::record_panic(panic_msg);
 }
 
 pub fn set_panic_handler() {
@@ -17,3 +22,24 @@ pub mod abi;
 
 ///The runtime module provides an interface to interact with the chain in the contract
 pub mod runtime;
+
+///The error module provides structured errors returned by the contract
+pub mod error;
+
+///The console module provides leveled and formatted logging on top of `runtime::debug`
+pub mod console;
+
+///The types module provides common data types used in the contract
+pub mod types;
+
+///The macros module provides procedural macros for contract development
+pub mod macros {
+    pub use oscore_macros::{base58, contract};
+}
+
+///The storage module provides typed collections kept in the contract storage
+pub mod storage;
+
+///The mock module provides an in-process host, so contracts can be tested natively with the `std` feature
+#[cfg(feature = "std")]
+pub mod mock;
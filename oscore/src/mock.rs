@@ -0,0 +1,404 @@
+use crate::error::ErrorInfo;
+use crate::types::Address;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+///How a contract execution driven by [`run`] ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    ///The entry point returned without calling `runtime::ret`
+    Finished,
+    ///The contract called `runtime::ret` with this payload
+    Return(Vec<u8>),
+    ///The contract called `runtime::panic`, or panicked, with this message.
+    ///Like on chain, the storage writes and notifications of the execution are reverted.
+    Panic(String),
+    ///The contract aborted with an error in the layout of `error::ContractError`, the execution is reverted
+    ///like a panic.
+    Error(ErrorInfo),
+}
+
+type ContractHandler = dyn Fn(&[u8]) -> Vec<u8>;
+
+#[derive(Default)]
+struct Host {
+    input: Vec<u8>,
+    debug: Vec<String>,
+    notifications: Vec<Vec<u8>>,
+    storage: BTreeMap<Vec<u8>, Vec<u8>>,
+    timestamp: u64,
+    block_height: u32,
+    tx_hash: [u8; 32],
+    block_hash: [u8; 32],
+    self_address: Address,
+    caller_address: Address,
+    entry_address: Address,
+    witnesses: Vec<Address>,
+    contracts: BTreeMap<Address, Rc<ContractHandler>>,
+    call_output: Vec<u8>,
+    panic_msg: Option<String>,
+    running: bool,
+    // the contract called `ret` or `panic`: on chain nothing runs after, so the writes of the destructors
+    // run by the unwinding are dropped
+    exited: bool,
+}
+
+thread_local! {
+    static HOST: RefCell<Host> = RefCell::new(Host::default());
+}
+
+fn with_host<T>(f: impl FnOnce(&mut Host) -> T) -> T {
+    HOST.with(|host| f(&mut host.borrow_mut()))
+}
+
+///Unwinding payload used to leave the contract from `ret` and `panic`
+struct Exit(Outcome);
+
+fn exit(outcome: Outcome) -> ! {
+    with_host(|host| host.exited = host.running);
+    panic::resume_unwind(Box::new(Exit(outcome)))
+}
+
+///Clear all the state of the mock host on the current thread.
+pub fn reset() {
+    with_host(|host| *host = Host::default());
+}
+
+///Set the bytes returned by `runtime::input`.
+/// # Example
+/// ```
+/// # use oscore::{mock, runtime};
+/// mock::set_input("hello");
+/// assert_eq!(runtime::input(), b"hello".to_vec());
+/// ```
+pub fn set_input(data: impl AsRef<[u8]>) {
+    let data = data.as_ref().to_vec();
+    with_host(|host| host.input = data);
+}
+
+///Set the values returned by `runtime::timestamp` and `runtime::block_height`.
+/// # Example
+/// ```
+/// # use oscore::{mock, runtime};
+/// mock::set_block(1_600_000_000, 100);
+/// assert_eq!(runtime::timestamp(), 1_600_000_000);
+/// assert_eq!(runtime::block_height(), 100);
+/// ```
+pub fn set_block(timestamp: u64, height: u32) {
+    with_host(|host| {
+        host.timestamp = timestamp;
+        host.block_height = height;
+    });
+}
+
+///Set the hash returned by `runtime::current_tx_hash`.
+pub fn set_tx_hash(hash: [u8; 32]) {
+    with_host(|host| host.tx_hash = hash);
+}
+
+///Set the hash returned by `runtime::current_block_hash`.
+pub fn set_block_hash(hash: [u8; 32]) {
+    with_host(|host| host.block_hash = hash);
+}
+
+///Set the address returned by `runtime::self_address`.
+pub fn set_self_address(addr: Address) {
+    with_host(|host| host.self_address = addr);
+}
+
+///Set the addresses returned by `runtime::caller_address` and `runtime::entry_address`.
+pub fn set_caller(caller: Address, entry: Address) {
+    with_host(|host| {
+        host.caller_address = caller;
+        host.entry_address = entry;
+    });
+}
+
+///Set the addresses accepted by `runtime::check_witness`.
+/// # Example
+/// ```
+/// # use oscore::{mock, runtime};
+/// # use oscore::types::Address;
+/// let owner = Address::new([1; 20]);
+/// mock::set_witnesses(&[owner]);
+/// assert!(runtime::check_witness(&owner));
+/// assert!(!runtime::check_witness(&Address::new([2; 20])));
+/// ```
+pub fn set_witnesses(addrs: &[Address]) {
+    let addrs = addrs.to_vec();
+    with_host(|host| host.witnesses = addrs);
+}
+
+///Register the handler executed when the contract at the address is invoked by `runtime::call_contract`.
+///
+///Calling an address without handler panics.
+/// # Example
+/// ```
+/// # use oscore::{mock, runtime};
+/// # use oscore::types::Address;
+/// let echo = Address::new([1; 20]);
+/// mock::register_contract(echo, |input| input.to_vec());
+/// assert_eq!(runtime::call_contract(&echo, b"ping"), b"ping".to_vec());
+/// ```
+pub fn register_contract(addr: Address, handler: impl Fn(&[u8]) -> Vec<u8> + 'static) {
+    with_host(|host| host.contracts.insert(addr, Rc::new(handler)));
+}
+
+///Get all the messages printed by `runtime::debug` so far.
+pub fn debug_messages() -> Vec<String> {
+    with_host(|host| host.debug.clone())
+}
+
+///Get all the notifications sent by `runtime::notify` so far.
+/// # Example
+/// ```
+/// # use oscore::{mock, runtime};
+/// runtime::notify(b"hello");
+/// assert_eq!(mock::notifications(), vec![b"hello".to_vec()]);
+/// ```
+pub fn notifications() -> Vec<Vec<u8>> {
+    with_host(|host| host.notifications.clone())
+}
+
+///Get a snapshot of the contract storage.
+/// # Example
+/// ```
+/// # use oscore::{mock, runtime};
+/// runtime::storage_write(b"key", b"value");
+/// assert_eq!(mock::storage().get(b"key".as_ref()), Some(&b"value".to_vec()));
+/// ```
+pub fn storage() -> BTreeMap<Vec<u8>, Vec<u8>> {
+    with_host(|host| host.storage.clone())
+}
+
+///Execute a contract entry point against the mock host and report how it ended.
+///
+///`runtime::ret` and `runtime::panic` never return, so the mock host leaves the entry point by unwinding
+///and `run` catches it. Like on chain, where destructors do not run after `ret`, the storage writes and
+///notifications made while unwinding are ignored. The state of the host is kept between calls, use
+///[`reset`] to clear it.
+/// # Example
+/// ```
+/// # use oscore::{mock, runtime};
+/// # use oscore::error::ErrorInfo;
+/// mock::set_input("ping");
+/// let outcome = mock::run(|| {
+///     let input = runtime::input();
+///     runtime::ret(&input);
+/// });
+/// assert_eq!(outcome, mock::Outcome::Return(b"ping".to_vec()));
+///
+/// let outcome = mock::run(|| runtime::panic("invalid param"));
+/// assert_eq!(outcome, mock::Outcome::Panic("invalid param".to_string()));
+///
+/// let err = ErrorInfo { code: 1, message: "invalid param".to_string() };
+/// let outcome = mock::run(|| runtime::panic_with_error(&err));
+/// assert_eq!(outcome, mock::Outcome::Error(err));
+/// ```
+pub fn run<F: FnOnce()>(f: F) -> Outcome {
+    let (storage, notified) = with_host(|host| {
+        host.running = true;
+        host.exited = false;
+        host.panic_msg = None;
+        (host.storage.clone(), host.notifications.len())
+    });
+    let res = panic::catch_unwind(AssertUnwindSafe(f));
+    let panic_msg = with_host(|host| {
+        host.running = false;
+        host.exited = false;
+        host.panic_msg.take()
+    });
+    let outcome = match res {
+        Ok(()) => Outcome::Finished,
+        Err(payload) => match payload.downcast::<Exit>() {
+            Ok(exit) => exit.0,
+            Err(payload) => Outcome::Panic(panic_msg.unwrap_or_else(|| {
+                if let Some(msg) = payload.downcast_ref::<&str>() {
+                    msg.to_string()
+                } else if let Some(msg) = payload.downcast_ref::<String>() {
+                    msg.clone()
+                } else {
+                    String::new()
+                }
+            })),
+        },
+    };
+    if let Outcome::Panic(_) | Outcome::Error(_) = outcome {
+        with_host(|host| {
+            host.storage = storage;
+            host.notifications.truncate(notified);
+        });
+    }
+    outcome
+}
+
+///Called by `oscore::panic_handler` instead of `runtime::panic`, which can not unwind from a panic hook.
+pub(crate) fn record_panic(msg: String) {
+    let running = with_host(|host| {
+        if host.running {
+            host.panic_msg = Some(msg.clone());
+        }
+        host.running
+    });
+    if !running {
+        eprintln!("{}", msg);
+    }
+}
+
+///Native replacement of the host imports
+pub(crate) mod env {
+    use super::{exit, with_host, Outcome};
+    use crate::error;
+    use crate::types::Address;
+    use sha2::{Digest, Sha256};
+    use std::slice;
+
+    unsafe fn bytes<'a>(ptr: *const u8, len: u32) -> &'a [u8] {
+        if len == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(ptr, len as usize)
+        }
+    }
+
+    pub unsafe fn oscore_return(ptr: *const u8, len: u32) -> ! {
+        exit(Outcome::Return(bytes(ptr, len).to_vec()))
+    }
+
+    pub unsafe fn oscore_panic(ptr: *const u8, len: u32) -> ! {
+        let data = bytes(ptr, len);
+        match error::decode_error(data) {
+            Some(err) => exit(Outcome::Error(err)),
+            None => exit(Outcome::Panic(String::from_utf8_lossy(data).into_owned())),
+        }
+    }
+
+    pub unsafe fn oscore_input_length() -> u32 {
+        with_host(|host| host.input.len() as u32)
+    }
+
+    pub unsafe fn oscore_get_input(dst: *mut u8) {
+        with_host(|host| {
+            std::ptr::copy_nonoverlapping(host.input.as_ptr(), dst, host.input.len());
+        })
+    }
+
+    pub unsafe fn oscore_sha256(data: *const u8, len: u32, val: *mut u8) {
+        let hash = Sha256::digest(bytes(data, len));
+        std::ptr::copy_nonoverlapping(hash.as_ptr(), val, hash.len());
+    }
+
+    pub unsafe fn oscore_debug(data: *const u8, len: u32) {
+        let msg = String::from_utf8_lossy(bytes(data, len)).into_owned();
+        with_host(|host| host.debug.push(msg));
+    }
+
+    pub unsafe fn oscore_notify(data: *const u8, len: u32) {
+        let data = bytes(data, len).to_vec();
+        with_host(|host| {
+            if !host.exited {
+                host.notifications.push(data)
+            }
+        });
+    }
+
+    pub unsafe fn oscore_timestamp() -> u64 {
+        with_host(|host| host.timestamp)
+    }
+
+    pub unsafe fn oscore_block_height() -> u32 {
+        with_host(|host| host.block_height)
+    }
+
+    pub unsafe fn oscore_current_txhash(dst: *mut u8) {
+        with_host(|host| std::ptr::copy_nonoverlapping(host.tx_hash.as_ptr(), dst, 32))
+    }
+
+    pub unsafe fn oscore_current_blockhash(dst: *mut u8) {
+        with_host(|host| std::ptr::copy_nonoverlapping(host.block_hash.as_ptr(), dst, 32))
+    }
+
+    pub unsafe fn oscore_self_address(dst: *mut u8) {
+        with_host(|host| {
+            std::ptr::copy_nonoverlapping(host.self_address.as_ref().as_ptr(), dst, 20)
+        })
+    }
+
+    pub unsafe fn oscore_caller_address(dst: *mut u8) {
+        with_host(|host| {
+            std::ptr::copy_nonoverlapping(host.caller_address.as_ref().as_ptr(), dst, 20)
+        })
+    }
+
+    pub unsafe fn oscore_entry_address(dst: *mut u8) {
+        with_host(|host| {
+            std::ptr::copy_nonoverlapping(host.entry_address.as_ref().as_ptr(), dst, 20)
+        })
+    }
+
+    pub unsafe fn oscore_check_witness(addr: *const u8) -> u32 {
+        let mut buf = [0; 20];
+        buf.copy_from_slice(bytes(addr, 20));
+        let addr = Address::new(buf);
+        with_host(|host| host.witnesses.contains(&addr) as u32)
+    }
+
+    pub unsafe fn oscore_call_contract(addr: *const u8, input: *const u8, len: u32) {
+        let mut buf = [0; 20];
+        buf.copy_from_slice(bytes(addr, 20));
+        let addr = Address::new(buf);
+        let handler = with_host(|host| host.contracts.get(&addr).cloned());
+        let handler = handler.unwrap_or_else(|| panic!("contract {:?} not found", addr));
+        let output = handler(bytes(input, len));
+        with_host(|host| host.call_output = output);
+    }
+
+    pub unsafe fn oscore_call_output_length() -> u32 {
+        with_host(|host| host.call_output.len() as u32)
+    }
+
+    pub unsafe fn oscore_get_call_output(dst: *mut u8) {
+        with_host(|host| {
+            std::ptr::copy_nonoverlapping(host.call_output.as_ptr(), dst, host.call_output.len());
+        })
+    }
+
+    pub unsafe fn oscore_storage_read(
+        key: *const u8,
+        klen: u32,
+        val: *mut u8,
+        vlen: u32,
+        offset: u32,
+    ) -> u32 {
+        let key = bytes(key, klen);
+        with_host(|host| match host.storage.get(key) {
+            None => u32::MAX,
+            Some(data) => {
+                let start = (offset as usize).min(data.len());
+                let n = (vlen as usize).min(data.len() - start);
+                std::ptr::copy_nonoverlapping(data[start..].as_ptr(), val, n);
+                data.len() as u32
+            }
+        })
+    }
+
+    pub unsafe fn oscore_storage_write(key: *const u8, klen: u32, val: *const u8, vlen: u32) {
+        let (key, val) = (bytes(key, klen).to_vec(), bytes(val, vlen).to_vec());
+        with_host(|host| {
+            if !host.exited {
+                host.storage.insert(key, val);
+            }
+        });
+    }
+
+    pub unsafe fn oscore_storage_delete(key: *const u8, klen: u32) {
+        let key = bytes(key, klen);
+        with_host(|host| {
+            if !host.exited {
+                host.storage.remove(key);
+            }
+        });
+    }
+}
@@ -1,3 +1,4 @@
+#[cfg(not(feature = "std"))]
 mod env {
     extern "C" {
         pub fn oscore_return(ptr: *const u8, len: u32) -> !;
@@ -6,9 +7,37 @@ mod env {
         pub fn oscore_get_input(dst: *mut u8);
         pub fn oscore_sha256(data: *const u8, len: u32, val: *mut u8);
         pub fn oscore_debug(data: *const u8, len: u32);
+        pub fn oscore_storage_read(
+            key: *const u8,
+            klen: u32,
+            val: *mut u8,
+            vlen: u32,
+            offset: u32,
+        ) -> u32;
+        pub fn oscore_storage_write(key: *const u8, klen: u32, val: *const u8, vlen: u32);
+        pub fn oscore_storage_delete(key: *const u8, klen: u32);
+        pub fn oscore_timestamp() -> u64;
+        pub fn oscore_block_height() -> u32;
+        pub fn oscore_current_txhash(dst: *mut u8);
+        pub fn oscore_current_blockhash(dst: *mut u8);
+        pub fn oscore_self_address(dst: *mut u8);
+        pub fn oscore_caller_address(dst: *mut u8);
+        pub fn oscore_entry_address(dst: *mut u8);
+        pub fn oscore_check_witness(addr: *const u8) -> u32;
+        pub fn oscore_call_contract(addr: *const u8, input: *const u8, len: u32);
+        pub fn oscore_call_output_length() -> u32;
+        pub fn oscore_get_call_output(dst: *mut u8);
+        pub fn oscore_notify(data: *const u8, len: u32);
     }
 }
 
+#[cfg(feature = "std")]
+use crate::mock::env;
+
+use crate::abi::{self, Decoder, Encoder, Error, Sink, Source};
+use crate::error::{self, ContractError};
+use crate::types::Address;
+
 /// Calculate the hash value
 /// # Example
 ///
@@ -25,6 +54,168 @@ pub fn sha256(data: impl AsRef<[u8]>) -> [u8; 32] {
     hash
 }
 
+/// Get the timestamp of the current block, in seconds since the unix epoch
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// let now = runtime::timestamp();
+/// ```
+pub fn timestamp() -> u64 {
+    unsafe { env::oscore_timestamp() }
+}
+
+/// Get the height of the current block
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// let height = runtime::block_height();
+/// ```
+pub fn block_height() -> u32 {
+    unsafe { env::oscore_block_height() }
+}
+
+/// Get the hash of the transaction being executed
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// let tx_hash = runtime::current_tx_hash();
+/// ```
+pub fn current_tx_hash() -> [u8; 32] {
+    let mut hash = [0; 32];
+    unsafe {
+        env::oscore_current_txhash(hash.as_mut_ptr());
+    }
+    hash
+}
+
+/// Get the hash of the current block
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// let block_hash = runtime::current_block_hash();
+/// ```
+pub fn current_block_hash() -> [u8; 32] {
+    let mut hash = [0; 32];
+    unsafe {
+        env::oscore_current_blockhash(hash.as_mut_ptr());
+    }
+    hash
+}
+
+/// Get the address of the current contract
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// let this = runtime::self_address();
+/// ```
+pub fn self_address() -> Address {
+    let mut addr = [0; 20];
+    unsafe {
+        env::oscore_self_address(addr.as_mut_ptr());
+    }
+    Address::new(addr)
+}
+
+/// Get the address of the caller, which is the contract calling the current contract or the address of
+/// the transaction initiator
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// let caller = runtime::caller_address();
+/// ```
+pub fn caller_address() -> Address {
+    let mut addr = [0; 20];
+    unsafe {
+        env::oscore_caller_address(addr.as_mut_ptr());
+    }
+    Address::new(addr)
+}
+
+/// Get the address of the first contract invoked by the transaction
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// let entry = runtime::entry_address();
+/// ```
+pub fn entry_address() -> Address {
+    let mut addr = [0; 20];
+    unsafe {
+        env::oscore_entry_address(addr.as_mut_ptr());
+    }
+    Address::new(addr)
+}
+
+/// Check whether the address authorized the current execution, either by signing the transaction or by
+/// being the contract calling the current contract
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// # use oscore::types::Address;
+/// let owner: Address = "AFmseVrdL9f9oyCzZefL9tG6UbvhUMqNMV".parse().unwrap();
+/// if !runtime::check_witness(&owner) {
+///     runtime::panic("no permission");
+/// }
+/// ```
+pub fn check_witness(addr: &Address) -> bool {
+    unsafe { env::oscore_check_witness(addr.as_bytes().as_ptr()) != 0 }
+}
+
+/// Invoke another contract with the input and get its return data, the whole execution is aborted if the
+/// called contract panics
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// # use oscore::types::Address;
+/// let registry = Address::new([1; 20]);
+/// let output = runtime::call_contract(&registry, b"ping");
+/// ```
+pub fn call_contract(addr: &Address, input: &[u8]) -> Vec<u8> {
+    let len = unsafe {
+        env::oscore_call_contract(addr.as_bytes().as_ptr(), input.as_ptr(), input.len() as u32);
+        env::oscore_call_output_length()
+    };
+
+    if len == 0 {
+        Vec::new()
+    } else {
+        let mut output = vec![0; len as usize];
+        unsafe {
+            env::oscore_get_call_output(output.as_mut_ptr());
+        }
+        output
+    }
+}
+
+/// Invoke another contract with the arguments encoded by `abi::Sink`, and decode its return data with
+/// `abi::decode_untrusted`: the output of another contract is untrusted, so it is decoded within the
+/// default `abi::DecodeLimits` and must not have trailing bytes
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// # use oscore::types::Address;
+/// let registry = Address::new([1; 20]);
+/// let user = Address::new([2; 20]);
+/// let verified: bool = runtime::call_contract_typed(&registry, ("is_verified", user)).unwrap();
+/// ```
+pub fn call_contract_typed<A: Encoder, R>(addr: &Address, args: A) -> Result<R, Error>
+where
+    for<'a> R: Decoder<'a>,
+{
+    let sink = Sink::with_value(&args);
+    let output = call_contract(addr, sink.bytes());
+    abi::decode_untrusted(&output)
+}
+
 /// Get input data from transaction or caller contract
 /// # Example
 ///
@@ -75,14 +266,223 @@ pub fn panic(msg: &str) -> ! {
     }
 }
 
-///Used to print the debug information in the contract, which can be seen in the log of the ontology node
+/// Abort the execution like `panic`, returning the error in the binary layout of `ContractError`
+///
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// # use oscore::error::ErrorInfo;
+///   runtime::panic_with_error(&ErrorInfo { code: 1, message: "invalid param".to_string() });
+/// ```
+pub fn panic_with_error<E: ContractError + ?Sized>(err: &E) -> ! {
+    let data = error::encode_error(err);
+    unsafe {
+        env::oscore_panic(data.as_ptr(), data.len() as u32);
+    }
+}
+
+/// Finish the execution with the result of an entry point: the value is encoded with `abi::Sink` and
+/// returned, the error aborts the execution through `panic_with_error`
+///
+/// # Example
+///
+/// ```no_run
+/// # use oscore::runtime;
+/// # use oscore::error::ErrorInfo;
+///   let res: Result<u32, ErrorInfo> = Ok(90);
+///   runtime::ret_result(res);
+/// ```
+pub fn ret_result<T: Encoder, E: ContractError>(res: Result<T, E>) -> ! {
+    match res {
+        Ok(val) => {
+            let sink = Sink::with_value(&val);
+            ret(sink.bytes())
+        }
+        Err(err) => panic_with_error(&err),
+    }
+}
+
+/// The value returned by a method of a `#[contract]`: an encodable value is returned as by `ret`, and a
+/// `Result` is passed to `ret_result`. The macro dispatches on this trait, so type aliases of `Result` work.
+pub trait MethodResult {
+    /// The type of the value returned to the caller, the `T` of `Result<T, E>`
+    type Value: Encoder;
+
+    /// Finish the execution with the value
+    fn ret(self) -> !;
+}
+
+impl<T: Encoder> MethodResult for T {
+    type Value = T;
+
+    fn ret(self) -> ! {
+        let sink = Sink::with_value(&self);
+        ret(sink.bytes())
+    }
+}
+
+impl<T: Encoder, E: ContractError> MethodResult for Result<T, E> {
+    type Value = T;
+
+    fn ret(self) -> ! {
+        ret_result(self)
+    }
+}
+
+/// Send a notification which can be subscribed by indexers, notifications are discarded if the execution
+/// fails
+/// # Example
+/// ```no_run
+/// # use oscore::runtime;
+/// runtime::notify(b"hello");
+/// ```
+pub fn notify(data: &[u8]) {
+    unsafe {
+        env::oscore_notify(data.as_ptr(), data.len() as u32);
+    }
+}
+
+/// An event of the contract, notified as its topic followed by the encoded event
+pub trait Event: Encoder {
+    /// The name indexers subscribe to
+    const TOPIC: &'static str;
+}
+
+/// Encode the event with `abi::Sink` and notify it
+/// # Example
+/// ```no_run
+/// # use oscore::runtime::{self, Event};
+/// # use oscore::abi::{Encoder, Sink};
+/// struct ScoreComputed {
+///     score: u32,
+/// }
+///
+/// impl Encoder for ScoreComputed {
+///     fn encode(&self, sink: &mut Sink) {
+///         sink.write(self.score);
+///     }
+/// }
+///
+/// impl Event for ScoreComputed {
+///     const TOPIC: &'static str = "score_computed";
+/// }
+///
+/// runtime::emit(&ScoreComputed { score: 90 });
+/// ```
+pub fn emit<E: Event>(event: &E) {
+    let mut sink = Sink::new(E::TOPIC.encoded_size() + event.encoded_size());
+    sink.write(E::TOPIC);
+    sink.write(event);
+    notify(sink.bytes());
+}
+
+///Used to print the debug information in the contract, which can be seen in the log of the ontology node.
+///The macros of `oscore::console` format and tag the message with a level.
 /// # Example
 /// ```no_run
-/// # use oscore::console;
-/// console::debug("test");
+/// # use oscore::runtime;
+/// runtime::debug("test");
 /// ```
 pub fn debug(msg: &str) {
     unsafe {
         env::oscore_debug(msg.as_ptr(), msg.len() as u32);
     }
 }
+
+/// Read the value stored under the key, return `None` if the key does not exist
+/// # Example
+/// ```no_run
+/// # use oscore::runtime;
+/// runtime::storage_write(b"key", b"value");
+/// assert_eq!(runtime::storage_read(b"key"), Some(b"value".to_vec()));
+/// ```
+pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
+    const INITIAL_SIZE: usize = 32;
+    let mut val = vec![0; INITIAL_SIZE];
+    let size = unsafe {
+        env::oscore_storage_read(
+            key.as_ptr(),
+            key.len() as u32,
+            val.as_mut_ptr(),
+            val.len() as u32,
+            0,
+        )
+    };
+    if size == u32::MAX {
+        return None;
+    }
+    let size = size as usize;
+    val.resize(size, 0);
+    if size > INITIAL_SIZE {
+        unsafe {
+            env::oscore_storage_read(
+                key.as_ptr(),
+                key.len() as u32,
+                val[INITIAL_SIZE..].as_mut_ptr(),
+                (size - INITIAL_SIZE) as u32,
+                INITIAL_SIZE as u32,
+            )
+        };
+    }
+    Some(val)
+}
+
+/// Store the value under the key, an existing value is overwritten
+/// # Example
+/// ```no_run
+/// # use oscore::runtime;
+/// runtime::storage_write(b"key", b"value");
+/// ```
+pub fn storage_write(key: &[u8], val: &[u8]) {
+    unsafe {
+        env::oscore_storage_write(
+            key.as_ptr(),
+            key.len() as u32,
+            val.as_ptr(),
+            val.len() as u32,
+        );
+    }
+}
+
+/// Remove the value stored under the key
+/// # Example
+/// ```no_run
+/// # use oscore::runtime;
+/// runtime::storage_delete(b"key");
+/// assert_eq!(runtime::storage_read(b"key"), None);
+/// ```
+pub fn storage_delete(key: &[u8]) {
+    unsafe {
+        env::oscore_storage_delete(key.as_ptr(), key.len() as u32);
+    }
+}
+
+/// Read the value stored under the key and decode it, the contract panics if the stored data can not be decoded
+/// # Example
+/// ```no_run
+/// # use oscore::runtime;
+/// runtime::put("total", 100u64);
+/// let total: Option<u64> = runtime::get("total");
+/// assert_eq!(total, Some(100));
+/// ```
+pub fn get<K: AsRef<[u8]>, T>(key: K) -> Option<T>
+where
+    for<'a> T: Decoder<'a>,
+{
+    storage_read(key.as_ref()).map(|val| {
+        let mut source = Source::new(&val);
+        source.read().expect("decode storage value failed")
+    })
+}
+
+/// Encode the value and store it under the key
+/// # Example
+/// ```no_run
+/// # use oscore::runtime;
+/// runtime::put("owner", "alice");
+/// ```
+pub fn put<K: AsRef<[u8]>, T: Encoder>(key: K, val: T) {
+    let sink = Sink::with_value(&val);
+    storage_write(key.as_ref(), sink.bytes());
+}
@@ -0,0 +1,348 @@
+use crate::abi::{Decoder, Encoder, Sink};
+use crate::runtime;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+///The kinds of keys, so collections sharing a prefix do not overwrite each other
+const VALUE: u8 = 0;
+const MAP_ENTRY: u8 = 1;
+const VEC_LEN: u8 = 2;
+const VEC_ELEM: u8 = 3;
+
+///The storage key of a collection: the length prefixed `prefix`, the kind of key and the encoded `key`.
+///The length prefix keeps a prefix followed by a key from colliding with a longer prefix.
+fn make_key<K: Encoder>(prefix: &[u8], kind: u8, key: K) -> Vec<u8> {
+    let mut sink = Sink::new(prefix.encoded_size() + 1 + key.encoded_size());
+    sink.write(prefix);
+    sink.write_byte(kind);
+    sink.write(key);
+    sink.into()
+}
+
+///A cached storage value and whether it has to be written back
+struct Entry<T> {
+    val: Option<T>,
+    dirty: bool,
+}
+
+impl<T: Encoder> Entry<T> {
+    fn load(key: &[u8]) -> Self
+    where
+        for<'a> T: Decoder<'a>,
+    {
+        Entry {
+            val: runtime::get(key),
+            dirty: false,
+        }
+    }
+
+    fn flush(&mut self, key: &[u8]) {
+        if self.dirty {
+            match &self.val {
+                Some(val) => runtime::put(key, val),
+                None => runtime::storage_delete(key),
+            }
+            self.dirty = false;
+        }
+    }
+}
+
+///A single value stored under a key derived from the prefix.
+///
+///The value is read from storage on first access, and modifications are only written back by `flush`.
+///Nothing is written when it is dropped: `runtime::ret` exits the contract without running destructors,
+///so a write on drop would be lost on chain. Call `flush` before returning.
+/// # Example
+/// ```no_run
+/// # use oscore::storage::StorageValue;
+/// let mut total: StorageValue<u64> = StorageValue::new("total");
+/// let val = total.get().copied().unwrap_or_default();
+/// total.set(val + 1);
+/// total.flush();
+/// ```
+pub struct StorageValue<T: Encoder> {
+    key: Vec<u8>,
+    entry: Option<Entry<T>>,
+}
+
+impl<T: Encoder> StorageValue<T>
+where
+    for<'a> T: Decoder<'a>,
+{
+    ///Create a value stored under the prefix, nothing is read until it is accessed.
+    pub fn new(prefix: impl AsRef<[u8]>) -> Self {
+        StorageValue {
+            key: make_key(prefix.as_ref(), VALUE, ()),
+            entry: None,
+        }
+    }
+
+    fn entry(&mut self) -> &mut Entry<T> {
+        let key = &self.key;
+        self.entry.get_or_insert_with(|| Entry::load(key))
+    }
+
+    ///Get the value, `None` if nothing is stored.
+    pub fn get(&mut self) -> Option<&T> {
+        self.entry().val.as_ref()
+    }
+
+    ///Get a mutable reference to the value, the value is written back on flush.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        let entry = self.entry();
+        entry.dirty = true;
+        entry.val.as_mut()
+    }
+
+    ///Replace the value.
+    pub fn set(&mut self, val: T) {
+        self.entry = Some(Entry {
+            val: Some(val),
+            dirty: true,
+        });
+    }
+
+    ///Remove the value from storage and return it.
+    pub fn remove(&mut self) -> Option<T> {
+        let entry = self.entry();
+        entry.dirty = true;
+        entry.val.take()
+    }
+}
+
+impl<T: Encoder> StorageValue<T> {
+    ///Write the modified value back to storage.
+    pub fn flush(&mut self) {
+        if let Some(entry) = &mut self.entry {
+            entry.flush(&self.key);
+        }
+    }
+}
+
+///A map whose entries are stored under keys derived from the prefix and the encoded key.
+///
+///Entries are read from storage on first access, and modifications are only written back by `flush`,
+///like `StorageValue`.
+/// # Example
+/// ```no_run
+/// # use oscore::storage::StorageMap;
+/// let mut scores: StorageMap<String, u32> = StorageMap::new("score");
+/// scores.insert(&"alice".to_string(), 90);
+/// assert_eq!(scores.get(&"alice".to_string()), Some(&90));
+/// scores.flush();
+/// ```
+pub struct StorageMap<K: Encoder, V: Encoder> {
+    prefix: Vec<u8>,
+    cache: BTreeMap<Vec<u8>, Entry<V>>,
+    _key: PhantomData<K>,
+}
+
+impl<K: Encoder, V: Encoder> StorageMap<K, V>
+where
+    for<'a> V: Decoder<'a>,
+{
+    ///Create a map stored under the prefix, nothing is read until it is accessed.
+    pub fn new(prefix: impl AsRef<[u8]>) -> Self {
+        StorageMap {
+            prefix: prefix.as_ref().to_vec(),
+            cache: BTreeMap::new(),
+            _key: PhantomData,
+        }
+    }
+
+    fn entry(&mut self, key: &K) -> &mut Entry<V> {
+        let key = make_key(&self.prefix, MAP_ENTRY, key);
+        self.cache
+            .entry(key)
+            .or_insert_with_key(|key| Entry::load(key))
+    }
+
+    ///Get the value of the key.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.entry(key).val.as_ref()
+    }
+
+    ///Get a mutable reference to the value of the key, the value is written back on flush.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let entry = self.entry(key);
+        entry.dirty = true;
+        entry.val.as_mut()
+    }
+
+    ///Check whether the key has a value.
+    pub fn contains_key(&mut self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    ///Set the value of the key.
+    pub fn insert(&mut self, key: &K, val: V) {
+        let key = make_key(&self.prefix, MAP_ENTRY, key);
+        self.cache.insert(
+            key,
+            Entry {
+                val: Some(val),
+                dirty: true,
+            },
+        );
+    }
+
+    ///Remove the key from storage and return its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.entry(key);
+        entry.dirty = true;
+        entry.val.take()
+    }
+}
+
+impl<K: Encoder, V: Encoder> StorageMap<K, V> {
+    ///Write all the modified entries back to storage.
+    pub fn flush(&mut self) {
+        for (key, entry) in self.cache.iter_mut() {
+            entry.flush(key);
+        }
+    }
+}
+
+///An indexable list, its length and its elements are stored under keys derived from the prefix, the key
+///of the element at `index` ends with the `u32` index.
+///
+///Elements are read from storage on first access, and modifications are only written back by `flush`,
+///like `StorageValue`.
+/// # Example
+/// ```no_run
+/// # use oscore::storage::StorageVec;
+/// let mut tasks: StorageVec<String> = StorageVec::new("tasks");
+/// tasks.push("review".to_string());
+/// let last = tasks.len() - 1;
+/// assert_eq!(tasks.get(last).map(|s| s.as_str()), Some("review"));
+/// tasks.flush();
+/// ```
+pub struct StorageVec<T: Encoder> {
+    prefix: Vec<u8>,
+    len: Option<Entry<u32>>,
+    cache: BTreeMap<u32, Entry<T>>,
+}
+
+impl<T: Encoder> StorageVec<T>
+where
+    for<'a> T: Decoder<'a>,
+{
+    ///Create a list stored under the prefix, nothing is read until it is accessed.
+    pub fn new(prefix: impl AsRef<[u8]>) -> Self {
+        StorageVec {
+            prefix: prefix.as_ref().to_vec(),
+            len: None,
+            cache: BTreeMap::new(),
+        }
+    }
+
+    fn len_entry(&mut self) -> &mut Entry<u32> {
+        let prefix = &self.prefix;
+        self.len
+            .get_or_insert_with(|| Entry::load(&make_key(prefix, VEC_LEN, ())))
+    }
+
+    fn entry(&mut self, index: u32) -> &mut Entry<T> {
+        let prefix = &self.prefix;
+        self.cache
+            .entry(index)
+            .or_insert_with(|| Entry::load(&make_key(prefix, VEC_ELEM, index)))
+    }
+
+    ///Get the number of elements.
+    pub fn len(&mut self) -> u32 {
+        self.len_entry().val.unwrap_or(0)
+    }
+
+    ///Check whether the list has no element.
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+
+    ///Get the element at the index, `None` if the index is out of bounds.
+    pub fn get(&mut self, index: u32) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+        self.entry(index).val.as_ref()
+    }
+
+    ///Get a mutable reference to the element at the index, the element is written back on flush.
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        if index >= self.len() {
+            return None;
+        }
+        let entry = self.entry(index);
+        entry.dirty = true;
+        entry.val.as_mut()
+    }
+
+    ///Replace the element at the index.
+    ///
+    ///# Panics
+    ///Panics if the index is out of bounds.
+    pub fn set(&mut self, index: u32, val: T) {
+        let len = self.len();
+        assert!(
+            index < len,
+            "storage vec index {} out of bounds {}",
+            index,
+            len
+        );
+        self.cache.insert(
+            index,
+            Entry {
+                val: Some(val),
+                dirty: true,
+            },
+        );
+    }
+
+    ///Append an element to the end of the list.
+    ///
+    ///# Panics
+    ///Panics if the list already has `u32::MAX` elements.
+    pub fn push(&mut self, val: T) {
+        let len = self.len();
+        let new_len = len.checked_add(1).expect("storage vec is full");
+        self.cache.insert(
+            len,
+            Entry {
+                val: Some(val),
+                dirty: true,
+            },
+        );
+        *self.len_entry() = Entry {
+            val: Some(new_len),
+            dirty: true,
+        };
+    }
+
+    ///Remove the last element and return it, `None` if the list is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.len();
+        if len == 0 {
+            return None;
+        }
+        let entry = self.entry(len - 1);
+        entry.dirty = true;
+        let val = entry.val.take();
+        *self.len_entry() = Entry {
+            val: Some(len - 1),
+            dirty: true,
+        };
+        val
+    }
+}
+
+impl<T: Encoder> StorageVec<T> {
+    ///Write the length and all the modified elements back to storage.
+    pub fn flush(&mut self) {
+        if let Some(len) = &mut self.len {
+            len.flush(&make_key(&self.prefix, VEC_LEN, ()));
+        }
+        for (index, entry) in self.cache.iter_mut() {
+            entry.flush(&make_key(&self.prefix, VEC_ELEM, index));
+        }
+    }
+}
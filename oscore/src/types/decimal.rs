@@ -0,0 +1,339 @@
+use crate::abi::meta::TypeInfo;
+use crate::abi::{Decoder, Encoder, Error, ErrorKind, Sink, Source};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+///Errors of parsing a decimal string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseDecimalError {
+    Empty,
+    InvalidDigit,
+    ScaleTooLarge,
+    Overflow,
+}
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            ParseDecimalError::Empty => "empty decimal string",
+            ParseDecimalError::InvalidDigit => "invalid digit in decimal string",
+            ParseDecimalError::ScaleTooLarge => "too many fractional digits",
+            ParseDecimalError::Overflow => "decimal overflows u128",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for ParseDecimalError {}
+
+fn pow10(exp: u8) -> u128 {
+    10u128.pow(exp as u32)
+}
+
+///The full 256 bit product of `a` and `b`, as its high and low halves
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const LOW: u128 = u64::MAX as u128;
+    let (a1, a0) = (a >> 64, a & LOW);
+    let (b1, b0) = (b >> 64, b & LOW);
+    let (p00, p01, p10, p11) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+    let mid = (p00 >> 64) + (p01 & LOW) + (p10 & LOW);
+    let lo = (p00 & LOW) | (mid << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+///Divide the 256 bit number `(hi, lo)` by `d`, returning the quotient and the remainder.
+///Return `None` if the quotient does not fit in `u128`.
+fn div_wide((hi, lo): (u128, u128), d: u128) -> Option<(u128, u128)> {
+    if hi >= d {
+        return None;
+    }
+    let (mut quot, mut rem) = (0u128, hi);
+    for i in (0..128).rev() {
+        let carry = rem >> 127;
+        rem = (rem << 1) | ((lo >> i) & 1);
+        quot <<= 1;
+        if carry == 1 || rem >= d {
+            rem = rem.wrapping_sub(d);
+            quot |= 1;
+        }
+    }
+    Some((quot, rem))
+}
+
+///An unsigned fixed-point number, a `u128` mantissa with `scale` fractional digits.
+///
+///The arithmetic is exact and deterministic: it returns `None` if the result overflows, and results with more
+///fractional digits than both operands are truncated toward zero. Values are compared by value, so
+///`1.5` equals `1.50`, while the encoding keeps the scale: the mantissa as `u128` followed by the scale
+///in one byte.
+/// # Example
+/// ```
+/// # use oscore::types::Decimal;
+/// let price: Decimal = "12.50".parse().unwrap();
+/// let qty = Decimal::new(3, 0);
+/// let total = price.checked_mul(qty).unwrap();
+/// assert_eq!(total.to_string(), "37.50");
+/// assert_eq!(total, "37.5".parse().unwrap());
+/// assert_eq!(price.checked_div(Decimal::new(0, 0)), None);
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct Decimal {
+    mantissa: u128,
+    scale: u8,
+}
+
+impl Decimal {
+    ///The largest scale, `10^38` is the largest power of ten fitting in `u128`
+    pub const MAX_SCALE: u8 = 38;
+
+    ///Create the decimal `mantissa / 10^scale`, panic if the scale is larger than `MAX_SCALE`
+    pub const fn new(mantissa: u128, scale: u8) -> Self {
+        assert!(scale <= Self::MAX_SCALE, "decimal scale is too large");
+        Decimal { mantissa, scale }
+    }
+
+    pub fn mantissa(&self) -> u128 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    ///Change the scale, truncating the dropped digits. Return `None` if the mantissa overflows.
+    /// # Example
+    /// ```
+    /// # use oscore::types::Decimal;
+    /// let d = Decimal::new(1999, 3);
+    /// assert_eq!(d.rescale(1).unwrap().to_string(), "1.9");
+    /// assert_eq!(d.rescale(5).unwrap().to_string(), "1.99900");
+    /// ```
+    pub fn rescale(&self, scale: u8) -> Option<Decimal> {
+        if scale > Self::MAX_SCALE {
+            return None;
+        }
+        let mantissa = if scale >= self.scale {
+            self.mantissa.checked_mul(pow10(scale - self.scale))?
+        } else {
+            self.mantissa / pow10(self.scale - scale)
+        };
+        Some(Decimal { mantissa, scale })
+    }
+
+    ///The same value with the trailing zeros of the fraction removed
+    fn normalize(&self) -> Decimal {
+        let mut res = *self;
+        while res.scale > 0 {
+            let (quot, rem) = (res.mantissa / 10, res.mantissa % 10);
+            if rem != 0 {
+                break;
+            }
+            res.mantissa = quot;
+            res.scale -= 1;
+        }
+        res
+    }
+
+    ///The mantissas of both values at the larger scale of the two
+    fn align(&self, other: &Decimal) -> Option<(u128, u128, u8)> {
+        let scale = self.scale.max(other.scale);
+        let a = self.rescale(scale)?.mantissa;
+        let b = other.rescale(scale)?.mantissa;
+        Some((a, b, scale))
+    }
+
+    pub fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        let (a, b, scale) = self.align(&other)?;
+        Some(Decimal {
+            mantissa: a.checked_add(b)?,
+            scale,
+        })
+    }
+
+    pub fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        let (a, b, scale) = self.align(&other)?;
+        Some(Decimal {
+            mantissa: a.checked_sub(b)?,
+            scale,
+        })
+    }
+
+    ///Multiply, the result has the larger scale of the operands
+    /// # Example
+    /// ```
+    /// # use oscore::types::Decimal;
+    /// // the product of the mantissas overflows `u128`, the result does not
+    /// let d = Decimal::from(1000u32).rescale(18).unwrap();
+    /// assert_eq!(d.checked_mul(d).unwrap().to_string(), "1000000.000000000000000000");
+    /// ```
+    pub fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        let product = mul_wide(self.mantissa, other.mantissa);
+        let (mantissa, _) = div_wide(product, pow10(self.scale.min(other.scale)))?;
+        Some(Decimal {
+            mantissa,
+            scale: self.scale.max(other.scale),
+        })
+    }
+
+    ///Divide, the result has the larger scale of the operands. Return `None` if `other` is zero.
+    pub fn checked_div(self, other: Decimal) -> Option<Decimal> {
+        if other.mantissa == 0 {
+            return None;
+        }
+        let scale = self.scale.max(other.scale);
+        // self / other = self.mantissa * 10^(other.scale + scale - self.scale) / other.mantissa / 10^scale
+        let exp = other.scale + scale - self.scale;
+        if exp <= Self::MAX_SCALE {
+            let (mantissa, _) = div_wide(mul_wide(self.mantissa, pow10(exp)), other.mantissa)?;
+            return Some(Decimal { mantissa, scale });
+        }
+        // 10^exp does not fit in u128: divide in two steps, the second one carrying the remainder
+        let rest = exp - Self::MAX_SCALE;
+        let (quot, rem) = div_wide(
+            mul_wide(self.mantissa, pow10(Self::MAX_SCALE)),
+            other.mantissa,
+        )?;
+        let (low, _) = div_wide(mul_wide(rem, pow10(rest)), other.mantissa)?;
+        let mantissa = quot.checked_mul(pow10(rest))?.checked_add(low)?;
+        Some(Decimal { mantissa, scale })
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.normalize(), other.normalize());
+        // a mantissa overflowing when scaled up is larger than any mantissa
+        if a.scale >= b.scale {
+            match b.mantissa.checked_mul(pow10(a.scale - b.scale)) {
+                Some(m) => a.mantissa.cmp(&m),
+                None => Ordering::Less,
+            }
+        } else {
+            match a.mantissa.checked_mul(pow10(b.scale - a.scale)) {
+                Some(m) => m.cmp(&b.mantissa),
+                None => Ordering::Greater,
+            }
+        }
+    }
+}
+
+impl Hash for Decimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let norm = self.normalize();
+        norm.mantissa.hash(state);
+        norm.scale.hash(state);
+    }
+}
+
+macro_rules! impl_decimal_from {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Decimal {
+                fn from(val: $ty) -> Self {
+                    Decimal { mantissa: val as u128, scale: 0 }
+                }
+            }
+        )*
+    };
+}
+
+impl_decimal_from!(u8, u16, u32, u64, u128);
+
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseDecimalError::Empty);
+        }
+        let (int, frac) = match s.find('.') {
+            Some(i) => (&s[..i], &s[i + 1..]),
+            None => (s, ""),
+        };
+        if int.is_empty() || (s.contains('.') && frac.is_empty()) {
+            return Err(ParseDecimalError::InvalidDigit);
+        }
+        if frac.len() > Decimal::MAX_SCALE as usize {
+            return Err(ParseDecimalError::ScaleTooLarge);
+        }
+        let mut mantissa: u128 = 0;
+        for c in int.bytes().chain(frac.bytes()) {
+            if !c.is_ascii_digit() {
+                return Err(ParseDecimalError::InvalidDigit);
+            }
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add((c - b'0') as u128))
+                .ok_or(ParseDecimalError::Overflow)?;
+        }
+        Ok(Decimal {
+            mantissa,
+            scale: frac.len() as u8,
+        })
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.mantissa.to_string();
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return f.write_str(&digits);
+        }
+        let padded = format!("{:0>width$}", digits, width = scale + 1);
+        let (int, frac) = padded.split_at(padded.len() - scale);
+        write!(f, "{}.{}", int, frac)
+    }
+}
+
+impl fmt::Debug for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Decimal({})", self)
+    }
+}
+
+impl Encoder for Decimal {
+    fn encode(&self, sink: &mut Sink) {
+        sink.write(self.mantissa);
+        sink.write_byte(self.scale);
+    }
+
+    fn encoded_size(&self) -> usize {
+        17
+    }
+}
+
+impl<'a> Decoder<'a> for Decimal {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        let mantissa = source.read_u128()?;
+        let pos = source.position();
+        let scale = source.read_byte()?;
+        if scale > Decimal::MAX_SCALE {
+            return Err(Error::new(ErrorKind::IrregularData, pos));
+        }
+        Ok(Decimal { mantissa, scale })
+    }
+}
+
+impl TypeInfo for Decimal {
+    fn type_name() -> String {
+        "Decimal".to_string()
+    }
+}
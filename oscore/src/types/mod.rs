@@ -0,0 +1,150 @@
+mod base58;
+mod decimal;
+
+pub use self::decimal::{Decimal, ParseDecimalError};
+
+use crate::abi::meta::TypeInfo;
+use crate::abi::{Decoder, Encoder, Error, Sink, Source};
+use crate::runtime;
+use std::fmt;
+use std::str::FromStr;
+
+const ADDR_VERSION: u8 = 0x17;
+
+///Errors of parsing a base58 address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base58Error {
+    InvalidChar,
+    InvalidLength,
+    InvalidVersion,
+    InvalidChecksum,
+}
+
+impl fmt::Display for Base58Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Base58Error::InvalidChar => "invalid base58 character",
+            Base58Error::InvalidLength => "invalid address length",
+            Base58Error::InvalidVersion => "invalid address version",
+            Base58Error::InvalidChecksum => "invalid address checksum",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for Base58Error {}
+
+///The 20 bytes address of an account or a contract.
+///
+///Addresses are displayed in base58 with a version byte and a checksum of double sha256, so formatting
+///and parsing need the `runtime::sha256` host function. Use `oscore::macros::base58!` for constant addresses.
+/// # Example
+/// ```no_run
+/// # use oscore::types::Address;
+/// let addr: Address = "AFmseVrdL9f9oyCzZefL9tG6UbvhUMqNMV".parse().unwrap();
+/// assert_eq!(addr, Address::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]));
+/// assert_eq!(addr.to_base58(), "AFmseVrdL9f9oyCzZefL9tG6UbvhUMqNMV");
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Address([u8; 20]);
+
+impl Address {
+    ///Create an address from its bytes
+    pub const fn new(bytes: [u8; 20]) -> Self {
+        Address(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    ///Parse a base58 address, checking its version and checksum
+    pub fn from_base58(s: &str) -> Result<Self, Base58Error> {
+        let data = base58::decode(s).ok_or(Base58Error::InvalidChar)?;
+        if data.len() != 25 {
+            return Err(Base58Error::InvalidLength);
+        }
+        if data[0] != ADDR_VERSION {
+            return Err(Base58Error::InvalidVersion);
+        }
+        if checksum(&data[..21]) != data[21..] {
+            return Err(Base58Error::InvalidChecksum);
+        }
+        let mut addr = [0; 20];
+        addr.copy_from_slice(&data[1..21]);
+        Ok(Address(addr))
+    }
+
+    ///Format the address in base58
+    pub fn to_base58(&self) -> String {
+        let mut data = Vec::with_capacity(25);
+        data.push(ADDR_VERSION);
+        data.extend_from_slice(&self.0);
+        let sum = checksum(&data);
+        data.extend_from_slice(&sum);
+        base58::encode(&data)
+    }
+}
+
+fn checksum(data: &[u8]) -> [u8; 4] {
+    let hash = runtime::sha256(runtime::sha256(data));
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+impl AsRef<[u8]> for Address {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; 20]> for Address {
+    fn from(bytes: [u8; 20]) -> Self {
+        Address(bytes)
+    }
+}
+
+impl FromStr for Address {
+    type Err = Base58Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Address::from_base58(s)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_base58())
+    }
+}
+
+impl fmt::Debug for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Address(")?;
+        for b in self.0.iter() {
+            write!(f, "{:02x}", b)?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl Encoder for Address {
+    fn encode(&self, sink: &mut Sink) {
+        sink.write_bytes(&self.0)
+    }
+
+    fn encoded_size(&self) -> usize {
+        20
+    }
+}
+
+impl<'a> Decoder<'a> for Address {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        source.read().map(Address)
+    }
+}
+
+impl TypeInfo for Address {
+    fn type_name() -> String {
+        "Address".to_string()
+    }
+}
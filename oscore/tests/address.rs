@@ -0,0 +1,89 @@
+//! Tests of the base58 address format, the checksum uses `runtime::sha256` of the mock host.
+use oscore::macros::base58;
+use oscore::runtime;
+use oscore::types::{Address, Base58Error};
+
+const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+///A plain base58 encoder, to build malformed addresses
+fn encode(data: &[u8]) -> String {
+    let mut num: Vec<u8> = data.to_vec();
+    let mut digits = Vec::new();
+    while num.iter().any(|&b| b != 0) {
+        let mut rem = 0u32;
+        for b in num.iter_mut() {
+            let acc = (rem << 8) | *b as u32;
+            *b = (acc / 58) as u8;
+            rem = acc % 58;
+        }
+        digits.push(ALPHABET[rem as usize]);
+    }
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    digits.resize(digits.len() + zeros, b'1');
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+fn with_checksum(version: u8, addr: [u8; 20]) -> Vec<u8> {
+    let mut data = vec![version];
+    data.extend_from_slice(&addr);
+    let hash = runtime::sha256(runtime::sha256(&data));
+    data.extend_from_slice(&hash[..4]);
+    data
+}
+
+#[test]
+fn base58_roundtrip() {
+    let one = Address::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(one.to_base58(), "AFmseVrdL9f9oyCzZefL9tG6UbvhUMqNMV");
+    assert_eq!(base58!("AFmseVrdL9f9oyCzZefL9tG6UbvhUMqNMV"), one);
+    for bytes in [[0; 20], [0xff; 20], [7; 20]] {
+        let addr = Address::new(bytes);
+        assert_eq!(addr.to_base58(), encode(&with_checksum(0x17, bytes)));
+        assert_eq!(addr.to_base58().parse(), Ok(addr));
+        assert_eq!(addr.to_string(), addr.to_base58());
+    }
+}
+
+#[test]
+fn base58_macro_matches_from_base58() {
+    let parse = |s: &str| Address::from_base58(s).unwrap();
+    assert_eq!(
+        base58!("AFmseVrdL9f9oyCzZefL9tG6UbvhPbdYzM"),
+        parse("AFmseVrdL9f9oyCzZefL9tG6UbvhPbdYzM")
+    );
+    assert_eq!(
+        base58!("Af7Udc9v3L82dQM5b4zee1Xt77Be1DFb61"),
+        parse("Af7Udc9v3L82dQM5b4zee1Xt77Be1DFb61")
+    );
+    let addr = base58!("AFsCjUGzicZmXQtWpwVt6fQTZyaVe7bfEk");
+    assert_eq!(addr, parse("AFsCjUGzicZmXQtWpwVt6fQTZyaVe7bfEk"));
+    let mut bytes = [0; 20];
+    bytes.iter_mut().zip(1..).for_each(|(b, i)| *b = i);
+    assert_eq!(addr, Address::new(bytes));
+}
+
+#[test]
+fn base58_errors() {
+    assert_eq!(
+        Address::from_base58("AFmseVrdL9f9oyCzZefL9tG6UbvhUMqNM0"),
+        Err(Base58Error::InvalidChar)
+    );
+    assert_eq!(Address::from_base58(""), Err(Base58Error::InvalidLength));
+    let mut long = with_checksum(0x17, [1; 20]);
+    long.push(0);
+    assert_eq!(
+        Address::from_base58(&encode(&long)),
+        Err(Base58Error::InvalidLength)
+    );
+    assert_eq!(
+        Address::from_base58(&encode(&with_checksum(0x00, [1; 20]))),
+        Err(Base58Error::InvalidVersion)
+    );
+    let mut data = with_checksum(0x17, [1; 20]);
+    data[24] ^= 1;
+    assert_eq!(
+        Address::from_base58(&encode(&data)),
+        Err(Base58Error::InvalidChecksum)
+    );
+}
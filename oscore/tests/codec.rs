@@ -0,0 +1,149 @@
+//! Tests of the layout and the decoding errors of the builtin codecs.
+mod common;
+
+use common::{decode_err, encode};
+use oscore::abi::{decode_all, Error, ErrorKind, Sink, Source};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+#[test]
+fn signed_layout() {
+    assert_eq!(encode(&-1i8), [0xff]);
+    assert_eq!(encode(&-2i16), [0xfe, 0xff]);
+    assert_eq!(encode(&i32::MIN), [0, 0, 0, 0x80]);
+    assert_eq!(encode(&-1i64), [0xff; 8]);
+    assert_eq!(encode(&i128::MAX)[15], 0x7f);
+    assert_eq!(decode_all::<i32>(&[0xff, 0xff, 0xff, 0xff]).unwrap(), -1);
+    assert_eq!(decode_all::<i16>(&[0, 0x80]).unwrap(), i16::MIN);
+}
+
+#[test]
+fn signed_truncated() {
+    for len in 0..8 {
+        let err = decode_err::<i64>(&[0xff; 8][..len]);
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEOF);
+    }
+    assert_eq!(decode_err::<i8>(&[]).kind(), ErrorKind::UnexpectedEOF);
+}
+
+#[test]
+fn maps_are_sorted() {
+    let map: HashMap<u8, bool> = vec![(3, true), (1, false), (2, true)].into_iter().collect();
+    let bytes = encode(&map);
+    assert_eq!(bytes, [3, 1, 0, 2, 1, 3, 1]);
+    let tree: BTreeMap<u8, bool> = map.clone().into_iter().collect();
+    assert_eq!(encode(&tree), bytes);
+    assert_eq!(decode_all::<HashMap<u8, bool>>(&bytes).unwrap(), map);
+
+    let set: HashSet<&str> = vec!["b", "a"].into_iter().collect();
+    assert_eq!(encode(&set), [2, 1, b'a', 1, b'b']);
+    let tree: BTreeSet<&str> = set.iter().copied().collect();
+    assert_eq!(encode(&tree), encode(&set));
+}
+
+#[test]
+fn unsorted_or_duplicate_keys_are_rejected() {
+    // the error is at the start of the map
+    let err = decode_err::<BTreeMap<u8, bool>>(&[2, 2, 0, 1, 0]);
+    assert_eq!((err.kind(), err.position()), (ErrorKind::IrregularData, 0));
+    let err = decode_err::<(u8, HashMap<u8, bool>)>(&[9, 2, 1, 0, 1, 1]);
+    assert_eq!((err.kind(), err.position()), (ErrorKind::IrregularData, 1));
+    let err = decode_err::<BTreeSet<u16>>(&[2, 5, 0, 5, 0]);
+    assert_eq!(err.kind(), ErrorKind::IrregularData);
+    let err = decode_err::<HashSet<u16>>(&[2, 6, 0, 5, 0]);
+    assert_eq!(err.kind(), ErrorKind::IrregularData);
+    // the entries are decoded before the order is checked
+    let err = decode_err::<BTreeMap<u8, bool>>(&[2, 1, 0, 2]);
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEOF);
+}
+
+#[test]
+fn error_positions_and_paths() {
+    let err = decode_err::<String>(&[2, 0xc3, 0x28]);
+    assert_eq!((err.kind(), err.position()), (ErrorKind::InvalidUtf8, 0));
+    let err = decode_err::<(u8, String)>(&[1, 1, 0xff]);
+    assert_eq!((err.kind(), err.position()), (ErrorKind::InvalidUtf8, 1));
+
+    let err = decode_err::<Vec<Option<bool>>>(&[3, 0, 1, 1, 1, 7]);
+    assert_eq!(err.kind(), ErrorKind::IrregularData);
+    assert_eq!(err.position(), 5);
+    assert_eq!(err.path().collect::<Vec<_>>(), ["Vec[2]", "Option"]);
+    assert_eq!(
+        err.to_string(),
+        "irregular data at byte 5 in Vec[2] > Option"
+    );
+
+    let err = decode_err::<Option<u8>>(&[2]);
+    assert_eq!((err.kind(), err.position()), (ErrorKind::IrregularData, 0));
+    assert_eq!(err.path().count(), 0);
+    assert_eq!(err.to_string(), "irregular data at byte 0");
+
+    let err = decode_err::<u32>(&[1, 2]);
+    assert_eq!((err.kind(), err.position()), (ErrorKind::UnexpectedEOF, 0));
+    let err = decode_err::<Vec<u8>>(&[5, 1, 2]);
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEOF);
+}
+
+#[test]
+fn error_context() {
+    let err = Error::new(ErrorKind::TypeInconsistency, 3)
+        .context("Inner.field")
+        .context(String::from("Outer.inner"));
+    assert_eq!(
+        err.path().collect::<Vec<_>>(),
+        ["Outer.inner", "Inner.field"]
+    );
+    assert_eq!(
+        err.to_string(),
+        "type inconsistency at byte 3 in Outer.inner > Inner.field"
+    );
+}
+
+#[test]
+fn trailing_bytes() {
+    let err = decode_all::<(u8, u16)>(&[1, 2, 0, 9, 9]).unwrap_err();
+    assert_eq!((err.kind(), err.position()), (ErrorKind::TrailingBytes, 3));
+    assert_eq!(decode_all::<(u8, u16)>(&[1, 2, 0]).unwrap(), (1, 2));
+    assert_eq!(decode_all::<()>(&[]).unwrap(), ());
+    assert_eq!(decode_all::<()>(&[0]).unwrap_err().position(), 0);
+    // a decoding error is reported before the trailing bytes
+    let err = decode_all::<bool>(&[2, 0]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::IrregularData);
+
+    let mut source = Source::new(&[1, 2]);
+    assert_eq!((source.remaining(), source.is_empty()), (2, false));
+    source.read::<u8>().unwrap();
+    assert_eq!(source.finish().unwrap_err().position(), 1);
+    source.read::<u8>().unwrap();
+    assert!(source.is_empty());
+    source.finish().unwrap();
+}
+
+#[test]
+fn slice_sink_overflow() {
+    let mut buf = [0u8; 4];
+    let mut sink = Sink::from_slice(&mut buf);
+    sink.write(1u32);
+    assert_eq!(sink.bytes(), [1, 0, 0, 0]);
+    sink.write(2u8);
+    assert_eq!(sink.len(), 5);
+    let err = sink.finish().unwrap_err();
+    assert_eq!((err.needed, err.capacity), (5, 4));
+}
+
+#[test]
+#[should_panic(expected = "buffer overflow: 5 bytes needed, capacity is 4")]
+fn slice_sink_overflow_bytes() {
+    let mut buf = [0u8; 4];
+    let mut sink = Sink::from_slice(&mut buf);
+    sink.write((1u32, 2u8));
+    sink.bytes();
+}
+
+#[test]
+#[should_panic(expected = "buffer overflow: 3 bytes needed, capacity is 2")]
+fn slice_sink_overflow_into() {
+    let mut buf = [0u8; 2];
+    let mut sink = Sink::from_slice(&mut buf);
+    sink.write("ab");
+    sink.into();
+}
@@ -0,0 +1,16 @@
+//! Helpers shared by the codec tests.
+// each test crate uses a part of the helpers
+#![allow(dead_code)]
+use oscore::abi::{Decoder, Encoder, Error, Sink, Source};
+
+pub fn encode<T: Encoder>(val: &T) -> Vec<u8> {
+    Sink::with_value(val).into()
+}
+
+///Decode `bytes` as `T`, panic if it succeeds
+pub fn decode_err<T: for<'a> Decoder<'a>>(bytes: &[u8]) -> Error {
+    match Source::new(bytes).read::<T>() {
+        Ok(_) => panic!("decoded {:?}", bytes),
+        Err(err) => err,
+    }
+}
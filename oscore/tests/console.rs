@@ -0,0 +1,43 @@
+//! Tests of the logging macros on the mock host.
+use oscore::console::{self, Level};
+use oscore::mock;
+
+#[test]
+fn lines_are_tagged() {
+    mock::reset();
+    console::error!("invalid param: {}", "sig");
+    console::warn!("no task since {} days", 90);
+    console::info!("score: {}", 90);
+    console::debug!("contribution: {}", 80);
+    console::log!(Level::Info, "plain");
+    assert_eq!(
+        mock::debug_messages(),
+        vec![
+            "[ERROR] console: invalid param: sig",
+            "[WARN] console: no task since 90 days",
+            "[INFO] console: score: 90",
+            "[DEBUG] console: contribution: 80",
+            "[INFO] console: plain",
+        ]
+    );
+}
+
+#[test]
+fn levels() {
+    assert!(Level::Error < Level::Warn && Level::Info < Level::Debug);
+    assert_eq!(Level::Warn.to_string(), "WARN");
+    // no max_level feature is enabled, so every level is compiled in
+    assert_eq!(console::MAX_LEVEL, Level::Debug as u8);
+}
+
+mod nested {
+    #[test]
+    fn module_path() {
+        oscore::mock::reset();
+        oscore::console::info!("hi");
+        assert_eq!(
+            oscore::mock::debug_messages(),
+            vec!["[INFO] console::nested: hi"]
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Tests of the `invoke` entry point generated by `#[contract]`, driven on the mock host.
+use oscore::abi::meta::Contract;
+use oscore::abi::{Encoder, Sink};
+use oscore::error::ErrorInfo;
+use oscore::macros::contract;
+use oscore::mock::{self, Outcome};
+use oscore::runtime;
+
+type ScoreResult<T> = Result<T, ErrorInfo>;
+
+#[derive(Default)]
+struct Score {
+    bonus: u32,
+}
+
+#[contract(abi)]
+impl Score {
+    pub fn add(a: u32, b: u32) -> u32 {
+        a + b
+    }
+
+    pub fn check(score: u32) -> Result<bool, ErrorInfo> {
+        if score > 100 {
+            return Err(ErrorInfo {
+                code: 1,
+                message: "invalid score".to_string(),
+            });
+        }
+        Ok(score >= 60)
+    }
+
+    pub fn checked_double(score: u32) -> ScoreResult<u32> {
+        score.checked_mul(2).ok_or(ErrorInfo {
+            code: 2,
+            message: "overflow".to_string(),
+        })
+    }
+
+    pub fn with_bonus(&self, score: u32) -> u32 {
+        score + self.bonus
+    }
+
+    pub fn reset(&mut self) {
+        runtime::storage_delete(b"score");
+    }
+
+    #[allow(dead_code)]
+    fn private() -> u32 {
+        0
+    }
+}
+
+fn call(args: impl Encoder) -> Outcome {
+    let mut sink = Sink::new(0);
+    sink.write(args);
+    mock::set_input(sink.bytes());
+    mock::run(invoke)
+}
+
+#[test]
+fn dispatch() {
+    mock::reset();
+    assert_eq!(call(("add", 2u32, 3u32)), Outcome::Return(vec![5, 0, 0, 0]));
+    assert_eq!(
+        call(("with_bonus", 7u32)),
+        Outcome::Return(vec![7, 0, 0, 0])
+    );
+
+    runtime::storage_write(b"score", b"1");
+    assert_eq!(call(("reset",)), Outcome::Return(Vec::new()));
+    assert_eq!(runtime::storage_read(b"score"), None);
+}
+
+#[test]
+fn unknown_method() {
+    mock::reset();
+    let not_found = Outcome::Panic("method not found".to_string());
+    assert_eq!(call(("sub", 2u32, 3u32)), not_found);
+    assert_eq!(call(("private",)), not_found);
+    assert_eq!(
+        call((0xFFu8,)),
+        Outcome::Panic("invalid method".to_string())
+    );
+    mock::set_input([]);
+    assert_eq!(
+        mock::run(invoke),
+        Outcome::Panic("invalid method".to_string())
+    );
+}
+
+#[test]
+fn invalid_params() {
+    mock::reset();
+    let invalid = Outcome::Panic("invalid param".to_string());
+    assert_eq!(call(("add", 2u32)), invalid);
+    assert_eq!(call(("add", 2u32, 3u16)), invalid);
+    // bytes left after the arguments
+    assert_eq!(call(("add", 2u32, 3u32, 0xFFu8)), invalid);
+    assert_eq!(call(("reset", 0u8)), invalid);
+}
+
+#[test]
+fn result_returns() {
+    mock::reset();
+    assert_eq!(call(("check", 90u32)), Outcome::Return(vec![1]));
+    assert_eq!(
+        call(("check", 101u32)),
+        Outcome::Error(ErrorInfo {
+            code: 1,
+            message: "invalid score".to_string()
+        })
+    );
+    // a type alias of `Result` is still finished with `ret_result`
+    assert_eq!(
+        call(("checked_double", 4u32)),
+        Outcome::Return(vec![8, 0, 0, 0])
+    );
+    assert_eq!(
+        call(("checked_double", u32::MAX)),
+        Outcome::Error(ErrorInfo {
+            code: 2,
+            message: "overflow".to_string()
+        })
+    );
+}
+
+#[test]
+fn abi_manifest() {
+    let abi = Score::abi();
+    assert_eq!(
+        abi.methods
+            .iter()
+            .map(|m| m.ret.as_str())
+            .collect::<Vec<_>>(),
+        ["u32", "bool", "u32", "u32", "()"]
+    );
+    assert_eq!(
+        abi.to_json(),
+        concat!(
+            r#"{"name":"Score","methods":["#,
+            r#"{"name":"add","args":[{"name":"a","type":"u32"},{"name":"b","type":"u32"}],"return":"u32"},"#,
+            r#"{"name":"check","args":[{"name":"score","type":"u32"}],"return":"bool"},"#,
+            r#"{"name":"checked_double","args":[{"name":"score","type":"u32"}],"return":"u32"},"#,
+            r#"{"name":"with_bonus","args":[{"name":"score","type":"u32"}],"return":"u32"},"#,
+            r#"{"name":"reset","args":[],"return":"()"}],"types":[]}"#
+        )
+    );
+}
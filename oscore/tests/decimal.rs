@@ -0,0 +1,110 @@
+//! Tests of the `Decimal` arithmetic, parsing and ordering.
+use oscore::types::{Decimal, ParseDecimalError};
+
+fn dec(s: &str) -> Decimal {
+    s.parse().unwrap()
+}
+
+#[test]
+fn mul_wide_intermediate() {
+    let d = Decimal::from(1000u32).rescale(18).unwrap();
+    let square = d.checked_mul(d).unwrap();
+    assert_eq!(square, Decimal::from(1_000_000u32));
+    assert_eq!(square.scale(), 18);
+
+    // the mantissas multiply to u128::MAX * 10^19, scaled back down by 10^19
+    let max = Decimal::new(u128::MAX, 19);
+    let one = Decimal::new(10u128.pow(19), 19);
+    assert_eq!(max.checked_mul(one), Some(max));
+    assert_eq!(one.checked_mul(max), Some(max));
+}
+
+#[test]
+fn mul_overflow_boundary() {
+    let max = Decimal::new(u128::MAX, 0);
+    assert_eq!(max.checked_mul(Decimal::from(1u8)), Some(max));
+    assert_eq!(max.checked_mul(Decimal::from(2u8)), None);
+    let half = Decimal::new(u128::MAX / 2, 0);
+    assert_eq!(
+        half.checked_mul(Decimal::from(2u8)).unwrap().mantissa(),
+        u128::MAX - 1
+    );
+    assert_eq!(
+        Decimal::new(u128::MAX / 2 + 1, 0).checked_mul(Decimal::from(2u8)),
+        None
+    );
+    // the result has the larger scale, 1.5 * u128::MAX overflows and 0.5 * u128::MAX truncates
+    assert_eq!(max.checked_mul(dec("0.5")), None);
+    let max = Decimal::new(u128::MAX, 1);
+    assert_eq!(max.checked_mul(dec("1.5")), None);
+    assert_eq!(
+        max.checked_mul(dec("0.5")).unwrap(),
+        Decimal::new(u128::MAX / 2, 1)
+    );
+    assert_eq!(
+        max.checked_mul(Decimal::new(0, 38)).unwrap(),
+        Decimal::from(0u8)
+    );
+}
+
+#[test]
+fn div_wide_intermediate() {
+    let d = Decimal::from(1_000_000u32).rescale(18).unwrap();
+    let thousand = Decimal::from(1000u32).rescale(18).unwrap();
+    assert_eq!(d.checked_div(thousand).unwrap(), Decimal::from(1000u32));
+
+    // 10^(38 + 38) does not fit in u128
+    let res = Decimal::from(1u8).checked_div(dec("3.00000000000000000000000000000000000000"));
+    assert_eq!(res.unwrap().to_string(), format!("0.{}", "3".repeat(38)));
+    let res = Decimal::from(1u8).checked_div(Decimal::new(7 * 10u128.pow(37), 38));
+    assert_eq!(
+        res.unwrap().to_string(),
+        "1.42857142857142857142857142857142857142"
+    );
+}
+
+#[test]
+fn div_overflow_boundary() {
+    let max = Decimal::new(u128::MAX, 0);
+    assert_eq!(max.checked_div(Decimal::from(1u8)), Some(max));
+    assert_eq!(max.checked_div(dec("0.5")), None);
+    assert_eq!(
+        max.checked_div(Decimal::from(2u8)).unwrap(),
+        Decimal::new(u128::MAX / 2, 0)
+    );
+    assert_eq!(Decimal::from(1u8).checked_div(Decimal::new(1, 38)), None);
+    assert_eq!(max.checked_div(Decimal::from(0u8)), None);
+    assert_eq!(max.checked_div(Decimal::new(0, 38)), None);
+}
+
+#[test]
+fn parse_scale_38() {
+    let d = dec("0.00000000000000000000000000000000000001");
+    assert_eq!((d.mantissa(), d.scale()), (1, 38));
+    let d = dec("3.40282366920938463463374607431768211455");
+    assert_eq!((d.mantissa(), d.scale()), (u128::MAX, 38));
+    let err = "3.40282366920938463463374607431768211456".parse::<Decimal>();
+    assert_eq!(err, Err(ParseDecimalError::Overflow));
+    let err = "0.000000000000000000000000000000000000001".parse::<Decimal>();
+    assert_eq!(err, Err(ParseDecimalError::ScaleTooLarge));
+    assert_eq!(d.to_string(), "3.40282366920938463463374607431768211455");
+}
+
+#[test]
+fn ord_across_scales() {
+    assert_eq!(dec("1.5"), dec("1.50000"));
+    assert!(dec("1.5") < dec("1.50001"));
+    assert!(dec("2") > dec("1.99999999999999999999999999999999999999"));
+    // scaling 10^20 up to scale 38 overflows u128, it is still the larger value
+    let big = Decimal::new(10u128.pow(20), 0);
+    let small = dec("3.40282366920938463463374607431768211455");
+    assert!(big > small);
+    assert!(small < big);
+    assert_eq!(
+        big.cmp(&big.rescale(10).unwrap()),
+        std::cmp::Ordering::Equal
+    );
+    let mut sorted = vec![dec("0.1"), dec("0.01"), Decimal::from(1u8), dec("0.10")];
+    sorted.sort();
+    assert_eq!(sorted, vec![dec("0.01"), dec("0.1"), dec("0.10"), dec("1")]);
+}
@@ -0,0 +1,282 @@
+//! Tests of the layout and the errors of `#[derive(Encoder, Decoder)]`.
+mod common;
+
+use common::{decode_err, encode};
+use oscore::abi::{decode_all, encode_versioned, Decoder, Encoder, Error, ErrorKind, Sink, Source};
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Task {
+    id: u32,
+    done: bool,
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Pair(u8, u16);
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Unit;
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+enum Shape {
+    Empty,
+    Circle(u8),
+    Rect { w: u8, h: u8 },
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Borrowed<'a> {
+    name: &'a str,
+    data: &'a [u8],
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq, Default)]
+struct Cached {
+    key: u8,
+    #[abi(skip)]
+    hits: u32,
+}
+
+mod percent {
+    use oscore::abi::{Error, Sink, Source};
+
+    pub fn encode(val: &u8, sink: &mut Sink) {
+        sink.write(*val as u32 * 100);
+    }
+
+    pub fn decode(source: &mut Source) -> Result<u8, Error> {
+        Ok((source.read::<u32>()? / 100) as u8)
+    }
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Rate {
+    #[abi(with = "percent")]
+    rate: u8,
+    flag: bool,
+}
+
+#[test]
+fn layout() {
+    assert_eq!(encode(&Task { id: 7, done: true }), [7, 0, 0, 0, 1]);
+    assert_eq!(encode(&Pair(1, 2)), [1, 2, 0]);
+    assert_eq!(encode(&Unit), [] as [u8; 0]);
+    assert_eq!(encode(&Shape::Empty), [0]);
+    assert_eq!(encode(&Shape::Circle(5)), [1, 5]);
+    assert_eq!(encode(&Shape::Rect { w: 2, h: 3 }), [2, 2, 3]);
+    assert_eq!(encode(&Wrapper { inner: 1u16 }), [1, 0]);
+    assert_eq!(
+        encode(&Borrowed {
+            name: "a",
+            data: &[9]
+        }),
+        [1, b'a', 1, 9]
+    );
+}
+
+#[test]
+fn roundtrip_borrowed_and_generic() {
+    let bytes = encode(&Borrowed {
+        name: "abc",
+        data: &[1, 2],
+    });
+    let val: Borrowed = decode_all(&bytes).unwrap();
+    assert_eq!((val.name, val.data), ("abc", &[1u8, 2][..]));
+    let bytes = encode(&Wrapper {
+        inner: Shape::Circle(1),
+    });
+    assert_eq!(
+        decode_all::<Wrapper<Shape>>(&bytes).unwrap(),
+        Wrapper {
+            inner: Shape::Circle(1)
+        }
+    );
+}
+
+#[test]
+fn skipped_fields() {
+    let bytes = encode(&Cached { key: 3, hits: 10 });
+    assert_eq!(bytes, [3]);
+    assert_eq!(Cached { key: 3, hits: 10 }.encoded_size(), 1);
+    assert_eq!(
+        decode_all::<Cached>(&bytes).unwrap(),
+        Cached { key: 3, hits: 0 }
+    );
+}
+
+#[test]
+fn with_fields() {
+    let val = Rate {
+        rate: 5,
+        flag: true,
+    };
+    let bytes = encode(&val);
+    assert_eq!(bytes, [244, 1, 0, 0, 1]);
+    assert_eq!(val.encoded_size(), 5);
+    assert_eq!(decode_all::<Rate>(&bytes).unwrap(), val);
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+#[abi(version = 1)]
+struct ItemV1 {
+    id: u8,
+    name: String,
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+#[abi(version = 3)]
+struct Item {
+    id: u8,
+    name: String,
+    #[abi(since = 2)]
+    price: u32,
+    #[abi(skip)]
+    cached: bool,
+    #[abi(since = 3)]
+    tags: Vec<u8>,
+}
+
+#[test]
+fn versioned_fields() {
+    let item = Item {
+        id: 1,
+        name: "a".to_string(),
+        price: 7,
+        cached: true,
+        tags: vec![9],
+    };
+    let bytes = encode(&item);
+    assert_eq!(bytes, [3, 9, 1, 1, b'a', 7, 0, 0, 0, 1, 9]);
+    assert_eq!(item.encoded_size(), bytes.len());
+
+    // new data decoded by an old struct skips the fields it does not know
+    let old = ItemV1 {
+        id: 1,
+        name: "a".to_string(),
+    };
+    assert_eq!(decode_all::<ItemV1>(&bytes).unwrap(), old);
+
+    // old data decoded by a new struct has the default values of the new fields
+    let item = decode_all::<Item>(&encode(&old)).unwrap();
+    assert_eq!((item.id, item.name.as_str()), (1, "a"));
+    assert_eq!((item.price, item.cached, item.tags), (0, false, vec![]));
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+#[abi(version = 1)]
+struct RateV1 {
+    #[abi(with = "percent")]
+    rate: u8,
+    flag: bool,
+}
+
+#[test]
+fn versioned_body_size() {
+    // the body is written directly into the sink with its size
+    let item = ItemV1 {
+        id: 1,
+        name: "abc".to_string(),
+    };
+    let mut buf = [0u8; 7];
+    let mut sink = Sink::from_slice(&mut buf);
+    sink.write(&item);
+    assert_eq!(sink.finish(), Ok(7));
+    assert_eq!(buf, [1, 5, 1, 3, b'a', b'b', b'c']);
+
+    // without the size of the `with` fields, the body is counted in a temporary buffer
+    let rate = RateV1 {
+        rate: 5,
+        flag: true,
+    };
+    let bytes = encode(&rate);
+    assert_eq!(bytes, [1, 5, 244, 1, 0, 0, 1]);
+    assert_eq!(rate.encoded_size(), 7);
+    assert_eq!(decode_all::<RateV1>(&bytes).unwrap(), rate);
+}
+
+///A value whose `encoded_size` is wrong
+#[derive(Debug, PartialEq)]
+struct Sloppy(u16);
+
+impl Encoder for Sloppy {
+    fn encode(&self, sink: &mut Sink) {
+        sink.write(self.0);
+    }
+
+    fn encoded_size(&self) -> usize {
+        1
+    }
+}
+
+impl<'a> Decoder<'a> for Sloppy {
+    fn decode(source: &mut Source<'a>) -> Result<Self, Error> {
+        source.read().map(Sloppy)
+    }
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+#[abi(version = 1)]
+struct Holder {
+    val: Sloppy,
+    flag: bool,
+}
+
+#[test]
+fn versioned_body_size_mismatch() {
+    let mut sink = Sink::new(0);
+    encode_versioned(&mut sink, 1, Some(2), |sink| sink.write(7u8));
+    assert_eq!(sink.bytes(), [1, 1, 7]);
+
+    // the body is encoded again with its actual size, in owned and slice sinks
+    let holder = Holder {
+        val: Sloppy(0x0102),
+        flag: true,
+    };
+    let bytes = encode(&holder);
+    assert_eq!(bytes, [1, 3, 2, 1, 1]);
+    assert_eq!(decode_all::<Holder>(&bytes).unwrap(), holder);
+    let mut buf = [0u8; 5];
+    let mut sink = Sink::from_slice(&mut buf);
+    sink.write(&holder);
+    assert_eq!(sink.finish(), Ok(5));
+    assert_eq!(buf, [1, 3, 2, 1, 1]);
+}
+
+#[test]
+fn unknown_variant() {
+    let err = decode_err::<Shape>(&[3]);
+    assert_eq!(err.kind(), ErrorKind::IrregularData);
+    assert_eq!(err.position(), 0);
+    let err = decode_err::<Vec<Shape>>(&[2, 0, 9]);
+    assert_eq!(err.position(), 2);
+    assert_eq!(err.to_string(), "irregular data at byte 2 in Vec[1]");
+}
+
+#[test]
+fn error_paths() {
+    let err = decode_err::<Task>(&[7, 0, 0, 0, 2]);
+    assert_eq!(err.path().collect::<Vec<_>>(), ["Task.done"]);
+    assert_eq!(err.position(), 4);
+
+    let err = decode_err::<Pair>(&[1, 2]);
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEOF);
+    assert_eq!(err.path().collect::<Vec<_>>(), ["Pair.1"]);
+
+    let err = decode_err::<Shape>(&[2, 1]);
+    assert_eq!(err.path().collect::<Vec<_>>(), ["Shape::Rect.h"]);
+    let err = decode_err::<Shape>(&[1]);
+    assert_eq!(err.path().collect::<Vec<_>>(), ["Shape::Circle.0"]);
+
+    let err = decode_err::<Wrapper<Task>>(&[0, 0, 0, 0, 5]);
+    assert_eq!(
+        err.to_string(),
+        "irregular data at byte 4 in Wrapper.inner > Task.done"
+    );
+
+    let err = decode_err::<Rate>(&[0, 0]);
+    assert_eq!(err.path().collect::<Vec<_>>(), ["Rate.rate"]);
+}
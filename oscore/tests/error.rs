@@ -0,0 +1,78 @@
+//! Tests of the binary layout of contract errors.
+use oscore::error::{self, ContractError, ErrorInfo, ERROR_MARKER};
+use oscore::mock::{self, Outcome};
+use oscore::runtime;
+
+enum ScoreError {
+    InvalidParam,
+    NoPermission,
+}
+
+impl ContractError for ScoreError {
+    fn code(&self) -> u32 {
+        match self {
+            ScoreError::InvalidParam => 1,
+            ScoreError::NoPermission => 0x0102,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ScoreError::InvalidParam => "invalid param".to_string(),
+            ScoreError::NoPermission => "no".to_string(),
+        }
+    }
+}
+
+#[test]
+fn layout() {
+    let data = error::encode_error(&ScoreError::NoPermission);
+    assert_eq!(data, [ERROR_MARKER, 2, 1, 0, 0, 2, b'n', b'o']);
+    assert_eq!(
+        error::decode_error(&data),
+        Some(ErrorInfo {
+            code: 0x0102,
+            message: "no".to_string()
+        })
+    );
+}
+
+#[test]
+fn malformed_errors_are_not_decoded() {
+    let data = error::encode_error(&ScoreError::InvalidParam);
+    assert_eq!(error::decode_error(b"invalid param"), None);
+    assert_eq!(error::decode_error(&[]), None);
+    for len in 1..data.len() {
+        assert_eq!(error::decode_error(&data[..len]), None, "prefix {}", len);
+    }
+    let mut trailing = data.clone();
+    trailing.push(0);
+    assert_eq!(error::decode_error(&trailing), None);
+    // a message which is not utf8
+    assert_eq!(
+        error::decode_error(&[ERROR_MARKER, 1, 0, 0, 0, 1, 0xff]),
+        None
+    );
+}
+
+#[test]
+fn ret_result() {
+    mock::reset();
+    let outcome = mock::run(|| runtime::ret_result::<u32, ScoreError>(Ok(90)));
+    assert_eq!(outcome, Outcome::Return(vec![90, 0, 0, 0]));
+
+    runtime::storage_write(b"key", b"value");
+    let outcome = mock::run(|| {
+        runtime::storage_delete(b"key");
+        runtime::ret_result::<u32, _>(Err(ScoreError::InvalidParam))
+    });
+    assert_eq!(
+        outcome,
+        Outcome::Error(ErrorInfo {
+            code: 1,
+            message: "invalid param".to_string()
+        })
+    );
+    // the error reverts the execution
+    assert_eq!(runtime::storage_read(b"key"), Some(b"value".to_vec()));
+}
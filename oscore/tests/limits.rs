@@ -0,0 +1,157 @@
+//! Tests of `DecodeLimits`.
+mod common;
+
+use common::encode;
+use oscore::abi::{
+    decode_all, decode_untrusted, DecodeLimits, Decoder, Encoder, ErrorKind, Source,
+};
+use oscore::macros::contract;
+use oscore::mock::{self, Outcome};
+
+fn limits(max_depth: u32, max_len: u64, max_alloc: usize) -> DecodeLimits {
+    DecodeLimits {
+        max_depth,
+        max_len,
+        max_alloc,
+    }
+}
+
+#[test]
+fn new_source_is_unlimited() {
+    assert_eq!(*Source::new(&[]).limits(), DecodeLimits::unlimited());
+    // more elements than the default `max_len`, as a stored value may have
+    let big = vec![1u8; (1 << 20) + 1];
+    let bytes = encode(&big);
+    assert_eq!(decode_all::<Vec<u8>>(&bytes).unwrap(), big);
+    let err = decode_untrusted::<Vec<u8>>(&bytes).unwrap_err();
+    assert_eq!((err.kind(), err.position()), (ErrorKind::LimitExceeded, 0));
+}
+
+#[test]
+fn max_depth() {
+    let bytes = encode(&Some(Some(Some(1u8))));
+    // each option and its content are one level deeper
+    let mut source = Source::new(&bytes).with_limits(limits(4, 10, 100));
+    assert_eq!(
+        source.read::<Option<Option<Option<u8>>>>().unwrap(),
+        Some(Some(Some(1)))
+    );
+    let mut source = Source::new(&bytes).with_limits(limits(3, 10, 100));
+    let err = source.read::<Option<Option<Option<u8>>>>().unwrap_err();
+    assert_eq!((err.kind(), err.position()), (ErrorKind::LimitExceeded, 2));
+    // the depth is released after each value
+    let bytes = encode(&(1u8, 2u8, 3u8, 4u8));
+    let mut source = Source::new(&bytes).with_limits(limits(2, 10, 100));
+    assert_eq!(source.read::<(u8, u8, u8, u8)>().unwrap(), (1, 2, 3, 4));
+}
+
+#[test]
+fn max_len() {
+    let bytes = encode(&vec![0u16; 3]);
+    let mut source = Source::new(&bytes).with_limits(limits(8, 3, 100));
+    assert_eq!(source.read::<Vec<u16>>().unwrap().len(), 3);
+    let mut source = Source::new(&bytes).with_limits(limits(8, 2, 100));
+    assert_eq!(
+        source.read::<Vec<u16>>().unwrap_err().kind(),
+        ErrorKind::LimitExceeded
+    );
+    // the length is checked before the elements are read
+    let mut source = Source::new(&[0xFE, 0, 0, 0, 1]).with_limits(limits(8, 2, 100));
+    assert_eq!(
+        source.read::<Vec<u8>>().unwrap_err().kind(),
+        ErrorKind::LimitExceeded
+    );
+}
+
+#[test]
+fn max_alloc() {
+    // the strings are 6 bytes each
+    let bytes = encode(&vec!["abcdef".to_string(); 3]);
+    let vec_size = 3 * std::mem::size_of::<String>();
+    let mut source = Source::new(&bytes).with_limits(limits(8, 10, vec_size + 18));
+    assert_eq!(source.read::<Vec<String>>().unwrap().len(), 3);
+    let mut source = Source::new(&bytes).with_limits(limits(8, 10, vec_size + 17));
+    let err = source.read::<Vec<String>>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+    assert_eq!(err.path().collect::<Vec<_>>(), ["Vec[2]"]);
+
+    // the budget is shared by all the values read from the source
+    let mut source = Source::new(&bytes[1..]).with_limits(limits(8, 10, 12));
+    source.read::<String>().unwrap();
+    source.read::<String>().unwrap();
+    assert_eq!(
+        source.read::<String>().unwrap_err().kind(),
+        ErrorKind::LimitExceeded
+    );
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+#[abi(version = 1)]
+struct Boxed {
+    s: String,
+}
+
+#[test]
+fn versioned_envelope_shares_limits() {
+    let strings = vec!["a".repeat(100); 10];
+    let err = decode_untrusted_with::<Vec<String>>(&encode(&strings), limits(8, 10, 600));
+    assert_eq!(err.unwrap_err().kind(), ErrorKind::LimitExceeded);
+
+    // the allocations in the bodies of the envelopes are charged to the same budget
+    let boxed: Vec<_> = strings.into_iter().map(|s| Boxed { s }).collect();
+    let bytes = encode(&boxed);
+    let err = decode_untrusted_with::<Vec<Boxed>>(&bytes, limits(8, 10, 600)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+    assert_eq!(err.path().collect::<Vec<_>>(), ["Vec[3]", "Boxed.s"]);
+    let total = 10 * std::mem::size_of::<Boxed>() + 1000;
+    assert_eq!(
+        decode_untrusted_with::<Vec<Boxed>>(&bytes, limits(8, 10, total)).unwrap(),
+        boxed
+    );
+
+    // and so is the nesting
+    let bytes = encode(&Some(Boxed { s: "a".to_string() }));
+    let err = decode_untrusted_with::<Option<Boxed>>(&bytes, limits(3, 10, 100)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+    assert_eq!(err.path().collect::<Vec<_>>(), ["Option", "Boxed.s"]);
+    assert!(decode_untrusted_with::<Option<Boxed>>(&bytes, limits(4, 10, 100)).is_ok());
+}
+
+fn decode_untrusted_with<'a, T: Decoder<'a>>(
+    bytes: &'a [u8],
+    limits: DecodeLimits,
+) -> Result<T, oscore::abi::Error> {
+    let mut source = Source::new(bytes).with_limits(limits);
+    let val = source.read()?;
+    source.finish()?;
+    Ok(val)
+}
+
+#[test]
+fn length_overflow() {
+    // u64::MAX elements of u32 overflow the allocated size
+    let data = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+    let err = decode_all::<Vec<u32>>(&data).unwrap_err();
+    assert_eq!((err.kind(), err.position()), (ErrorKind::LimitExceeded, 0));
+}
+
+struct Store;
+
+#[contract]
+impl Store {
+    pub fn count(items: Vec<u8>) -> u32 {
+        items.len() as u32
+    }
+}
+
+#[test]
+fn contract_input_is_limited() {
+    mock::reset();
+    mock::set_input(encode(&("count", vec![1u8; 3])));
+    assert_eq!(mock::run(invoke), Outcome::Return(vec![3, 0, 0, 0]));
+    mock::set_input(encode(&("count", vec![1u8; (1 << 20) + 1])));
+    assert_eq!(
+        mock::run(invoke),
+        Outcome::Panic("invalid param".to_string())
+    );
+}
@@ -0,0 +1,330 @@
+//! Property-based round-trip tests of the `Encoder` and `Decoder` impls: every value decodes to itself,
+//! and decoding then encoding gives back the same bytes, so the encoding is canonical.
+mod common;
+
+use common::encode;
+use oscore::abi::{decode_all, Decoder, Encoder, ErrorKind, Sink, Source};
+use oscore::types::{Address, Decimal};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Debug;
+
+const ROUNDS: usize = 200;
+
+fn roundtrip<T>(val: &T)
+where
+    T: Encoder + for<'a> Decoder<'a> + PartialEq + Debug,
+{
+    let bytes = encode(val);
+    let decoded: T = decode_all(&bytes).unwrap_or_else(|err| panic!("{:?}: {}", val, err));
+    assert_eq!(&decoded, val);
+    assert_eq!(encode(&decoded), bytes);
+
+    // the size is exact, and a buffer of that size is enough
+    assert_eq!(val.encoded_size(), bytes.len(), "encoded size of {:?}", val);
+    let mut buf = vec![0; bytes.len()];
+    let mut sink = Sink::from_slice(&mut buf);
+    sink.write(val);
+    assert_eq!(sink.finish(), Ok(bytes.len()));
+    assert_eq!(buf, bytes);
+}
+
+fn check<T, F>(rng: &fastrand::Rng, gen: F)
+where
+    T: Encoder + for<'a> Decoder<'a> + PartialEq + Debug,
+    F: Fn(&fastrand::Rng) -> T,
+{
+    for _ in 0..ROUNDS {
+        roundtrip(&gen(rng));
+    }
+}
+
+fn rng() -> fastrand::Rng {
+    let rng = fastrand::Rng::new();
+    rng.seed(0x05c0_4e00);
+    rng
+}
+
+fn gen_len(rng: &fastrand::Rng) -> usize {
+    // lengths around the one byte varuint boundary
+    match rng.u8(..8) {
+        0 => rng.usize(250..260),
+        _ => rng.usize(..16),
+    }
+}
+
+fn gen_bytes(rng: &fastrand::Rng) -> Vec<u8> {
+    (0..gen_len(rng)).map(|_| rng.u8(..)).collect()
+}
+
+fn gen_string(rng: &fastrand::Rng) -> String {
+    (0..gen_len(rng))
+        .map(|_| match rng.u8(..4) {
+            0 => std::char::from_u32(rng.u32(0x80..0x11_0000)).unwrap_or('\u{fffd}'),
+            _ => rng.alphanumeric(),
+        })
+        .collect()
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Order {
+    id: u64,
+    owner: Address,
+    price: Decimal,
+    items: Vec<(u32, String)>,
+    memo: Option<String>,
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+enum Event {
+    Created(u32),
+    Cancelled { id: u64, reason: String },
+    Settled,
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+#[abi(version = 2)]
+struct Account {
+    balance: Decimal,
+    #[abi(since = 2)]
+    frozen: bool,
+}
+
+fn gen_order(rng: &fastrand::Rng) -> Order {
+    let mut owner = [0; 20];
+    owner.iter_mut().for_each(|b| *b = rng.u8(..));
+    Order {
+        id: rng.u64(..),
+        owner: Address::new(owner),
+        price: Decimal::new(rng.u128(..), rng.u8(..=Decimal::MAX_SCALE)),
+        items: (0..rng.usize(..4))
+            .map(|_| (rng.u32(..), gen_string(rng)))
+            .collect(),
+        memo: if rng.bool() {
+            Some(gen_string(rng))
+        } else {
+            None
+        },
+    }
+}
+
+#[test]
+fn integers() {
+    let rng = rng();
+    check(&rng, |rng| rng.u8(..));
+    check(&rng, |rng| rng.u16(..));
+    check(&rng, |rng| rng.u32(..));
+    check(&rng, |rng| rng.u64(..));
+    check(&rng, |rng| rng.u128(..));
+    check(&rng, |rng| rng.i8(..));
+    check(&rng, |rng| rng.i16(..));
+    check(&rng, |rng| rng.i32(..));
+    check(&rng, |rng| rng.i64(..));
+    check(&rng, |rng| rng.i128(..));
+    check(&rng, |rng| rng.bool());
+    for val in [0, 1, u128::MAX, u64::MAX as u128 + 1] {
+        roundtrip(&val);
+    }
+    for val in [i64::MIN, -1, 0, i64::MAX] {
+        roundtrip(&val);
+    }
+}
+
+#[test]
+fn varuint_boundaries() {
+    let cases: &[(u64, &[u8])] = &[
+        (0, &[0]),
+        (0xFC, &[0xFC]),
+        (0xFD, &[0xFD, 0xFD, 0]),
+        (0xFFFF, &[0xFD, 0xFF, 0xFF]),
+        (0x1_0000, &[0xFE, 0, 0, 1, 0]),
+        (0xFFFF_FFFF, &[0xFE, 0xFF, 0xFF, 0xFF, 0xFF]),
+        (0x1_0000_0000, &[0xFF, 0, 0, 0, 0, 1, 0, 0, 0]),
+        (
+            u64::MAX,
+            &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+        ),
+    ];
+    for &(val, bytes) in cases {
+        let mut sink = Sink::new(0);
+        sink.write_varuint(val);
+        assert_eq!(sink.bytes(), bytes, "encoding of {:#x}", val);
+        let mut source = Source::new(bytes);
+        assert_eq!(source.read_varuint().unwrap(), val);
+        assert!(source.is_empty());
+    }
+
+    // a value in a longer form than needed is rejected
+    let long: &[&[u8]] = &[
+        &[0xFD, 0xFC, 0],
+        &[0xFE, 0xFF, 0xFF, 0, 0],
+        &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0],
+    ];
+    for bytes in long {
+        let err = Source::new(bytes).read_varuint().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::IrregularData);
+    }
+
+    let rng = rng();
+    for _ in 0..ROUNDS {
+        let val = match rng.u8(..4) {
+            0 => rng.u64(..0x100),
+            1 => rng.u64(..0x1_0000),
+            2 => rng.u64(..0x1_0000_0000),
+            _ => rng.u64(..),
+        };
+        let mut sink = Sink::new(0);
+        sink.write_varuint(val);
+        let mut source = Source::new(sink.bytes());
+        assert_eq!(source.read_varuint().unwrap(), val);
+        source.finish().unwrap();
+    }
+}
+
+#[test]
+fn length_boundaries() {
+    for (len, prefix) in [(0xFC, 1), (0xFD, 3), (0xFFFF, 3), (0x1_0000, 5)] {
+        let bytes = vec![7u8; len];
+        let encoded = encode(&bytes.as_slice());
+        assert_eq!(encoded.len() - len, prefix, "length prefix of {:#x}", len);
+        assert_eq!(decode_all::<&[u8]>(&encoded).unwrap(), bytes.as_slice());
+        roundtrip(&bytes);
+    }
+}
+
+#[test]
+fn bytes_and_strings() {
+    let rng = rng();
+    check(&rng, gen_bytes);
+    check(&rng, gen_string);
+    for _ in 0..ROUNDS {
+        let bytes = gen_bytes(&rng);
+        let encoded = encode(&bytes.as_slice());
+        assert_eq!(encoded, encode(&bytes));
+        assert_eq!(decode_all::<&[u8]>(&encoded).unwrap(), bytes.as_slice());
+
+        let s = gen_string(&rng);
+        let encoded = encode(&s.as_str());
+        assert_eq!(encoded, encode(&s));
+        assert_eq!(decode_all::<&str>(&encoded).unwrap(), s);
+    }
+}
+
+#[test]
+fn collections() {
+    let rng = rng();
+    check(&rng, |rng| {
+        (0..gen_len(rng)).map(|_| rng.u32(..)).collect::<Vec<_>>()
+    });
+    check(&rng, |rng| {
+        (0..rng.usize(..8))
+            .map(|_| gen_string(rng))
+            .collect::<Vec<_>>()
+    });
+    check(
+        &rng,
+        |rng| if rng.bool() { Some(rng.i32(..)) } else { None },
+    );
+    check(&rng, |rng| {
+        (0..rng.usize(..16))
+            .map(|_| (rng.u16(..), gen_string(rng)))
+            .collect::<BTreeMap<_, _>>()
+    });
+    check(&rng, |rng| {
+        (0..rng.usize(..16))
+            .map(|_| rng.i64(..))
+            .collect::<BTreeSet<_>>()
+    });
+    check(&rng, |rng| {
+        (0..rng.usize(..16))
+            .map(|_| (gen_string(rng), rng.bool()))
+            .collect::<HashMap<_, _>>()
+    });
+    check(&rng, |rng| {
+        (0..rng.usize(..16))
+            .map(|_| rng.u8(..))
+            .collect::<HashSet<_>>()
+    });
+}
+
+#[test]
+fn tuples_and_arrays() {
+    let rng = rng();
+    check(&rng, |rng| (rng.u8(..), gen_string(rng), rng.bool()));
+    check(&rng, |rng| ((rng.u64(..),), (rng.i16(..), rng.u128(..))));
+    check(&rng, |rng| {
+        let mut array = [0u8; 32];
+        array.iter_mut().for_each(|b| *b = rng.u8(..));
+        array
+    });
+    check(&rng, |rng| [rng.u8(..)]);
+    roundtrip(&());
+}
+
+#[test]
+fn oscore_types() {
+    let rng = rng();
+    check(&rng, |rng| {
+        let mut bytes = [0; 20];
+        bytes.iter_mut().for_each(|b| *b = rng.u8(..));
+        Address::new(bytes)
+    });
+    check(&rng, |rng| {
+        Decimal::new(rng.u128(..), rng.u8(..=Decimal::MAX_SCALE))
+    });
+}
+
+#[test]
+fn derived() {
+    let rng = rng();
+    check(&rng, gen_order);
+    check(&rng, |rng| match rng.u8(..3) {
+        0 => Event::Created(rng.u32(..)),
+        1 => Event::Cancelled {
+            id: rng.u64(..),
+            reason: gen_string(rng),
+        },
+        _ => Event::Settled,
+    });
+    check(&rng, |rng| Account {
+        balance: Decimal::new(rng.u128(..), rng.u8(..=Decimal::MAX_SCALE)),
+        frozen: rng.bool(),
+    });
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn floats() {
+    let rng = rng();
+    // NaN and infinities are not encodable
+    check(&rng, |rng| loop {
+        let val = f32::from_bits(rng.u32(..));
+        if val.is_finite() {
+            break val;
+        }
+    });
+    check(&rng, |rng| loop {
+        let val = f64::from_bits(rng.u64(..));
+        if val.is_finite() {
+            break val;
+        }
+    });
+    for val in [0.0, -0.0, f64::MIN_POSITIVE, f64::MAX, f64::MIN] {
+        roundtrip(&val);
+    }
+    for bits in [f64::NAN.to_bits(), f64::INFINITY.to_bits()] {
+        let err = decode_all::<f64>(&bits.to_le_bytes()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::IrregularData);
+    }
+}
+
+#[test]
+fn truncated_input() {
+    let rng = rng();
+    for _ in 0..ROUNDS {
+        let order = gen_order(&rng);
+        let bytes = encode(&order);
+        let cut = rng.usize(..bytes.len());
+        let err = decode_all::<Order>(&bytes[..cut]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEOF);
+    }
+}
@@ -0,0 +1,244 @@
+//! Tests of the runtime API on the mock host.
+use oscore::abi::{Encoder, ErrorKind, Sink, Source};
+use oscore::mock::{self, Outcome};
+use oscore::runtime::{self, Event};
+use oscore::types::Address;
+
+#[test]
+fn storage_read_write_delete() {
+    mock::reset();
+    assert_eq!(runtime::storage_read(b"key"), None);
+    runtime::storage_write(b"key", b"value");
+    assert_eq!(runtime::storage_read(b"key"), Some(b"value".to_vec()));
+    runtime::storage_write(b"key", b"");
+    assert_eq!(runtime::storage_read(b"key"), Some(Vec::new()));
+    runtime::storage_delete(b"key");
+    assert_eq!(runtime::storage_read(b"key"), None);
+    // deleting a missing key is not an error
+    runtime::storage_delete(b"key");
+}
+
+#[test]
+fn storage_read_large_value() {
+    mock::reset();
+    // values longer than the first read buffer are fetched in a second read
+    for len in [31, 32, 33, 1000] {
+        let val: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        runtime::storage_write(b"big", &val);
+        assert_eq!(runtime::storage_read(b"big"), Some(val));
+    }
+}
+
+#[test]
+fn typed_get_put() {
+    mock::reset();
+    runtime::put("total", 100u64);
+    assert_eq!(runtime::get::<_, u64>("total"), Some(100));
+    assert_eq!(runtime::get::<_, u64>("missing"), None);
+    runtime::put("owner", "alice");
+    assert_eq!(
+        runtime::get::<_, String>("owner"),
+        Some("alice".to_string())
+    );
+    assert_eq!(
+        mock::storage().get(b"owner".as_ref()),
+        Some(&b"\x05alice".to_vec())
+    );
+}
+
+#[test]
+fn get_undecodable_value_panics() {
+    mock::reset();
+    runtime::storage_write(b"total", &[1, 2]);
+    let outcome = mock::run(|| {
+        runtime::get::<_, u64>("total");
+    });
+    match outcome {
+        Outcome::Panic(msg) => assert!(msg.contains("decode storage value failed"), "{}", msg),
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+}
+
+#[test]
+fn writes_are_reverted_on_panic() {
+    mock::reset();
+    runtime::storage_write(b"kept", b"1");
+    let outcome = mock::run(|| {
+        runtime::storage_write(b"kept", b"2");
+        runtime::storage_write(b"dropped", b"3");
+        runtime::panic("abort");
+    });
+    assert_eq!(outcome, Outcome::Panic("abort".to_string()));
+    assert_eq!(runtime::storage_read(b"kept"), Some(b"1".to_vec()));
+    assert_eq!(runtime::storage_read(b"dropped"), None);
+
+    let outcome = mock::run(|| runtime::storage_write(b"kept", b"4"));
+    assert_eq!(outcome, Outcome::Finished);
+    assert_eq!(runtime::storage_read(b"kept"), Some(b"4".to_vec()));
+}
+
+#[test]
+fn block_and_transaction_context() {
+    mock::reset();
+    assert_eq!(runtime::timestamp(), 0);
+    assert_eq!(runtime::block_height(), 0);
+    assert_eq!(runtime::current_tx_hash(), [0; 32]);
+
+    mock::set_block(1_600_000_000, 100);
+    mock::set_tx_hash([1; 32]);
+    mock::set_block_hash([2; 32]);
+    assert_eq!(runtime::timestamp(), 1_600_000_000);
+    assert_eq!(runtime::block_height(), 100);
+    assert_eq!(runtime::current_tx_hash(), [1; 32]);
+    assert_eq!(runtime::current_block_hash(), [2; 32]);
+
+    mock::reset();
+    assert_eq!(runtime::block_height(), 0);
+    assert_eq!(runtime::current_block_hash(), [0; 32]);
+}
+
+#[test]
+fn sha256() {
+    let hash = runtime::sha256("abc");
+    assert_eq!(hash[..8], [0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea]);
+    assert_eq!(hash[28..], [0xf2, 0x00, 0x15, 0xad]);
+}
+
+#[test]
+fn addresses_and_witnesses() {
+    mock::reset();
+    let (this, caller, entry) = (
+        Address::new([1; 20]),
+        Address::new([2; 20]),
+        Address::new([3; 20]),
+    );
+    assert_eq!(runtime::self_address(), Address::default());
+    mock::set_self_address(this);
+    mock::set_caller(caller, entry);
+    assert_eq!(runtime::self_address(), this);
+    assert_eq!(runtime::caller_address(), caller);
+    assert_eq!(runtime::entry_address(), entry);
+
+    assert!(!runtime::check_witness(&caller));
+    mock::set_witnesses(&[caller, entry]);
+    assert!(runtime::check_witness(&caller));
+    assert!(runtime::check_witness(&entry));
+    assert!(!runtime::check_witness(&this));
+    mock::set_witnesses(&[]);
+    assert!(!runtime::check_witness(&caller));
+}
+
+#[test]
+fn check_witness_guards_execution() {
+    mock::reset();
+    let owner = Address::new([9; 20]);
+    let guarded = || {
+        if !runtime::check_witness(&owner) {
+            runtime::panic("no permission");
+        }
+        runtime::ret(b"ok");
+    };
+    assert_eq!(
+        mock::run(guarded),
+        Outcome::Panic("no permission".to_string())
+    );
+    mock::set_witnesses(&[owner]);
+    assert_eq!(mock::run(guarded), Outcome::Return(b"ok".to_vec()));
+}
+
+#[test]
+fn call_contract() {
+    mock::reset();
+    let echo = Address::new([1; 20]);
+    let silent = Address::new([2; 20]);
+    mock::register_contract(echo, |input| input.to_vec());
+    mock::register_contract(silent, |_| Vec::new());
+    assert_eq!(runtime::call_contract(&echo, b"ping"), b"ping".to_vec());
+    assert_eq!(runtime::call_contract(&silent, b"ping"), Vec::<u8>::new());
+
+    let missing = Address::new([3; 20]);
+    match mock::run(|| {
+        runtime::call_contract(&missing, b"ping");
+    }) {
+        Outcome::Panic(msg) => assert!(msg.contains("not found"), "{}", msg),
+        outcome => panic!("unexpected outcome {:?}", outcome),
+    }
+}
+
+#[test]
+fn call_contract_typed() {
+    mock::reset();
+    let adder = Address::new([1; 20]);
+    mock::register_contract(adder, |input| {
+        let mut source = Source::new(input);
+        let (method, a, b): (&str, u32, u32) = source.read().unwrap();
+        assert_eq!(method, "add");
+        let mut sink = Sink::new(4);
+        sink.write(a + b);
+        sink.into()
+    });
+    let sum: u32 = runtime::call_contract_typed(&adder, ("add", 2u32, 3u32)).unwrap();
+    assert_eq!(sum, 5);
+
+    // the output is too short for a u64
+    let err = runtime::call_contract_typed::<_, u64>(&adder, ("add", 2u32, 3u32)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEOF);
+    // and too long for a u16
+    let err = runtime::call_contract_typed::<_, u16>(&adder, ("add", 2u32, 3u32)).unwrap_err();
+    assert_eq!((err.kind(), err.position()), (ErrorKind::TrailingBytes, 2));
+
+    // the output of the callee is decoded within the default limits
+    let big = Address::new([2; 20]);
+    mock::register_contract(big, |_| {
+        let mut sink = Sink::new(0);
+        sink.write(vec![0u8; (1 << 20) + 1]);
+        sink.into()
+    });
+    let err = runtime::call_contract_typed::<_, Vec<u8>>(&big, "get").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+}
+
+#[derive(Encoder)]
+struct ScoreComputed {
+    user: Address,
+    score: u32,
+}
+
+impl Event for ScoreComputed {
+    const TOPIC: &'static str = "score_computed";
+}
+
+#[test]
+fn notify_and_emit() {
+    mock::reset();
+    runtime::notify(b"hello");
+    runtime::emit(&ScoreComputed {
+        user: Address::new([1; 20]),
+        score: 90,
+    });
+    let notifications = mock::notifications();
+    assert_eq!(notifications.len(), 2);
+    assert_eq!(notifications[0], b"hello");
+
+    let mut source = Source::new(&notifications[1]);
+    assert_eq!(source.read::<&str>().unwrap(), "score_computed");
+    assert_eq!(source.read::<Address>().unwrap(), Address::new([1; 20]));
+    assert_eq!(source.read::<u32>().unwrap(), 90);
+    source.finish().unwrap();
+}
+
+#[test]
+fn notifications_are_discarded_on_panic() {
+    mock::reset();
+    let outcome = mock::run(|| {
+        runtime::notify(b"kept");
+        runtime::ret(&[]);
+    });
+    assert_eq!(outcome, Outcome::Return(Vec::new()));
+    let outcome = mock::run(|| {
+        runtime::notify(b"dropped");
+        runtime::panic("abort");
+    });
+    assert_eq!(outcome, Outcome::Panic("abort".to_string()));
+    assert_eq!(mock::notifications(), vec![b"kept".to_vec()]);
+}
@@ -0,0 +1,147 @@
+//! Tests of the serde `Serializer` and `Deserializer`.
+#![cfg(feature = "serde")]
+use oscore::abi::serde::{from_bytes, to_bytes, Deserializer, Error};
+use oscore::abi::{DecodeLimits, ErrorKind, Source};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+fn limited<'a, T: Deserialize<'a>>(data: &'a [u8], max_alloc: usize) -> Result<T, Error> {
+    let limits = DecodeLimits {
+        max_alloc,
+        ..DecodeLimits::default()
+    };
+    let mut source = Source::new(data).with_limits(limits);
+    T::deserialize(&mut Deserializer::new(&mut source))
+}
+
+fn decode_kind(err: Error) -> (ErrorKind, usize) {
+    match err {
+        Error::Decode(err) => (err.kind(), err.position()),
+        err => panic!("not a decode error: {:?}", err),
+    }
+}
+
+#[test]
+fn roundtrip() {
+    let mut map = BTreeMap::new();
+    map.insert("one".to_string(), vec![1u32]);
+    map.insert("two".to_string(), vec![1, 2]);
+    let bytes = to_bytes(&map).unwrap();
+    assert_eq!(
+        from_bytes::<BTreeMap<String, Vec<u32>>>(&bytes).unwrap(),
+        map
+    );
+}
+
+#[test]
+fn owned_strings_are_charged() {
+    let bytes = to_bytes(&"a".repeat(100)).unwrap();
+    assert_eq!(limited::<String>(&bytes, 100).unwrap().len(), 100);
+    assert_eq!(
+        decode_kind(limited::<String>(&bytes, 99).unwrap_err()),
+        (ErrorKind::LimitExceeded, 101)
+    );
+    // borrowing allocates nothing
+    assert_eq!(limited::<&str>(&bytes, 0).unwrap().len(), 100);
+}
+
+#[test]
+fn owned_bytes_are_charged() {
+    let bytes = to_bytes(&serde_bytes_like(&[7; 50])).unwrap();
+    let err = limited::<ByteBuf>(&bytes, 49).unwrap_err();
+    assert_eq!(decode_kind(err).0, ErrorKind::LimitExceeded);
+    assert_eq!(limited::<ByteBuf>(&bytes, 50).unwrap().0, vec![7; 50]);
+}
+
+#[test]
+fn elements_are_charged() {
+    let bytes = to_bytes(&vec![1u64; 10]).unwrap();
+    assert_eq!(limited::<Vec<u64>>(&bytes, 80).unwrap().len(), 10);
+    assert_eq!(
+        decode_kind(limited::<Vec<u64>>(&bytes, 79).unwrap_err()).0,
+        ErrorKind::LimitExceeded
+    );
+
+    let strings = vec!["a".repeat(100); 10];
+    let bytes = to_bytes(&strings).unwrap();
+    let err = limited::<Vec<String>>(&bytes, 600).unwrap_err();
+    assert_eq!(decode_kind(err).0, ErrorKind::LimitExceeded);
+
+    let mut map = BTreeMap::new();
+    map.insert(1u32, 2u32);
+    map.insert(3, 4);
+    let bytes = to_bytes(&map).unwrap();
+    assert_eq!(limited::<BTreeMap<u32, u32>>(&bytes, 16).unwrap(), map);
+    assert_eq!(
+        decode_kind(limited::<BTreeMap<u32, u32>>(&bytes, 15).unwrap_err()).0,
+        ErrorKind::LimitExceeded
+    );
+}
+
+#[test]
+fn fields_are_not_charged() {
+    let bytes = to_bytes(&(1u64, 2u64)).unwrap();
+    assert_eq!(limited::<(u64, u64)>(&bytes, 0).unwrap(), (1, 2));
+}
+
+#[test]
+fn non_finite_floats_are_rejected() {
+    for &val in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert_eq!(
+            to_bytes(&val).unwrap_err(),
+            Error::Unsupported("NaN or infinite float")
+        );
+        assert!(to_bytes(&(val as f32)).is_err());
+
+        let bytes = val.to_bits().to_le_bytes();
+        assert_eq!(
+            decode_kind(from_bytes::<f64>(&bytes).unwrap_err()),
+            (ErrorKind::IrregularData, 0)
+        );
+        let bytes = [&[1][..], &(val as f32).to_bits().to_le_bytes()].concat();
+        let err = from_bytes::<(u8, f32)>(&bytes).unwrap_err();
+        assert_eq!(decode_kind(err), (ErrorKind::IrregularData, 1));
+    }
+    assert_eq!(from_bytes::<f64>(&to_bytes(&1.5f64).unwrap()).unwrap(), 1.5);
+}
+
+#[test]
+fn trailing_bytes_are_rejected() {
+    let mut bytes = to_bytes(&7u32).unwrap();
+    bytes.push(0);
+    assert_eq!(
+        decode_kind(from_bytes::<u32>(&bytes).unwrap_err()).0,
+        ErrorKind::TrailingBytes
+    );
+}
+
+///Owned bytes, deserialized with `deserialize_byte_buf`
+#[derive(Debug)]
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visit;
+        impl<'de> serde::de::Visitor<'de> for Visit {
+            type Value = ByteBuf;
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("bytes")
+            }
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<ByteBuf, E> {
+                Ok(ByteBuf(v.to_vec()))
+            }
+        }
+        deserializer.deserialize_byte_buf(Visit)
+    }
+}
+
+///Serialize bytes with `serialize_bytes`
+fn serde_bytes_like(bytes: &[u8]) -> impl serde::Serialize + '_ {
+    struct Bytes<'a>(&'a [u8]);
+    impl serde::Serialize for Bytes<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+    Bytes(bytes)
+}
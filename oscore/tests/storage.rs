@@ -0,0 +1,157 @@
+//! Tests of the storage collections on the mock host.
+use oscore::mock::{self, Outcome};
+use oscore::runtime;
+use oscore::storage::{StorageMap, StorageValue, StorageVec};
+
+#[test]
+fn value_is_persisted() {
+    mock::reset();
+    let mut total: StorageValue<u64> = StorageValue::new("total");
+    assert_eq!(total.get(), None);
+    total.set(5);
+    *total.get_mut().unwrap() += 1;
+    total.flush();
+
+    let mut total: StorageValue<u64> = StorageValue::new("total");
+    assert_eq!(total.get(), Some(&6));
+    assert_eq!(total.remove(), Some(6));
+    total.flush();
+    assert!(mock::storage().is_empty());
+}
+
+#[test]
+fn unflushed_changes_are_not_visible() {
+    mock::reset();
+    let mut a: StorageValue<u32> = StorageValue::new("a");
+    a.set(1);
+    assert_eq!(StorageValue::<u32>::new("a").get(), None);
+    a.flush();
+    assert_eq!(StorageValue::<u32>::new("a").get(), Some(&1));
+}
+
+#[test]
+fn drop_does_not_flush() {
+    mock::reset();
+    let mut value: StorageValue<u32> = StorageValue::new("value");
+    value.set(1);
+    drop(value);
+    let mut map: StorageMap<u8, u8> = StorageMap::new("map");
+    map.insert(&1, 2);
+    drop(map);
+    let mut list: StorageVec<u8> = StorageVec::new("list");
+    list.push(3);
+    drop(list);
+    assert!(mock::storage().is_empty());
+}
+
+#[test]
+fn map_entries() {
+    mock::reset();
+    let mut scores: StorageMap<String, u32> = StorageMap::new("score");
+    let (alice, bob) = ("alice".to_string(), "bob".to_string());
+    scores.insert(&alice, 90);
+    scores.insert(&bob, 70);
+    assert_eq!(scores.remove(&bob), Some(70));
+    assert!(!scores.contains_key(&bob));
+    scores.flush();
+
+    let mut scores: StorageMap<String, u32> = StorageMap::new("score");
+    assert_eq!(scores.get(&alice), Some(&90));
+    assert_eq!(scores.get(&bob), None);
+    assert_eq!(mock::storage().len(), 1);
+}
+
+#[test]
+fn vec_push_pop_set() {
+    mock::reset();
+    let mut tasks: StorageVec<String> = StorageVec::new("tasks");
+    assert!(tasks.is_empty());
+    assert_eq!(tasks.pop(), None);
+    tasks.push("a".to_string());
+    tasks.push("b".to_string());
+    tasks.push("c".to_string());
+    tasks.set(0, "z".to_string());
+    assert_eq!(tasks.pop(), Some("c".to_string()));
+    tasks.flush();
+
+    let mut tasks: StorageVec<String> = StorageVec::new("tasks");
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks.get(0).map(String::as_str), Some("z"));
+    assert_eq!(tasks.get(1).map(String::as_str), Some("b"));
+    assert_eq!(tasks.get(2), None);
+    assert_eq!(tasks.get_mut(2), None);
+}
+
+#[test]
+fn vec_set_out_of_bounds_panics() {
+    mock::reset();
+    let outcome = mock::run(|| {
+        let mut tasks: StorageVec<u8> = StorageVec::new("tasks");
+        tasks.set(0, 1);
+    });
+    assert_eq!(
+        outcome,
+        Outcome::Panic("storage vec index 0 out of bounds 0".to_string())
+    );
+}
+
+#[test]
+fn vec_push_full_panics() {
+    mock::reset();
+    // the length is stored under the length prefixed prefix followed by the kind of key 2
+    runtime::storage_write(b"\x05tasks\x02", &u32::MAX.to_le_bytes());
+    let outcome = mock::run(|| {
+        let mut tasks: StorageVec<u8> = StorageVec::new("tasks");
+        tasks.push(1);
+    });
+    assert_eq!(outcome, Outcome::Panic("storage vec is full".to_string()));
+    assert_eq!(StorageVec::<u8>::new("tasks").len(), u32::MAX);
+}
+
+#[test]
+fn prefixes_do_not_collide() {
+    mock::reset();
+    // the prefix "a" followed by the key b'b' must not be the key of the prefix "ab"
+    let mut map: StorageMap<u8, u32> = StorageMap::new("a");
+    map.insert(&b'b', 7);
+    map.flush();
+    assert_eq!(StorageValue::<u32>::new("ab").get(), None);
+
+    let mut value: StorageValue<u32> = StorageValue::new("ab");
+    value.set(8);
+    value.flush();
+    assert_eq!(map.get(&b'b'), Some(&7));
+}
+
+#[test]
+fn collections_sharing_a_prefix_do_not_collide() {
+    mock::reset();
+    let mut list: StorageVec<u32> = StorageVec::new("x");
+    list.push(1);
+    list.flush();
+    assert_eq!(StorageValue::<u32>::new("x").get(), None);
+
+    let mut value: StorageValue<u32> = StorageValue::new("x");
+    value.set(9);
+    value.flush();
+    let mut map: StorageMap<(), u32> = StorageMap::new("x");
+    assert_eq!(map.get(&()), None);
+    assert_eq!(StorageVec::<u32>::new("x").len(), 1);
+}
+
+#[test]
+fn unflushed_changes_are_lost_on_ret() {
+    mock::reset();
+    // like on chain, nothing is written after `ret`
+    let outcome = mock::run(|| {
+        let mut flushed: StorageValue<u32> = StorageValue::new("flushed");
+        flushed.set(1);
+        flushed.flush();
+        let mut lost: StorageValue<u32> = StorageValue::new("lost");
+        lost.set(2);
+        runtime::ret(b"done");
+    });
+    assert_eq!(outcome, Outcome::Return(b"done".to_vec()));
+    assert_eq!(StorageValue::<u32>::new("flushed").get(), Some(&1));
+    assert_eq!(StorageValue::<u32>::new("lost").get(), None);
+}